@@ -0,0 +1,198 @@
+use core::{hash::Hasher, time::Duration};
+
+use ahash::AHasher;
+use rand::RngCore;
+use vexide_async::time::sleep;
+
+#[cfg(feature = "crypto")]
+use crate::Secret;
+use crate::{backend::Instant, hasher, squeeze_256};
+
+/// Gathers entropy from an RNG over time until enough has been collected to produce a seed.
+///
+/// Each sample draws from the inner RNG and credits it with a fixed number of bits,
+/// usually the RNG's `estimated_entropy_bits`. Sampling can either be driven manually with
+/// [`sample`](Self::sample) (for example, once per frame while drawing a progress bar),
+/// or all at once with [`gather`](Self::gather) or [`gather_blocking`](Self::gather_blocking).
+#[derive(Debug, Clone)]
+pub struct EntropyAccumulator<R> {
+    rng: R,
+    hasher: AHasher,
+    bits_per_sample: f32,
+    accumulated_bits: f32,
+    sample_delay: Duration,
+}
+impl<R: RngCore> EntropyAccumulator<R> {
+    /// The default time [`gather`](Self::gather) waits between samples, so that sensor readings have a chance to change.
+    pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Create a new `EntropyAccumulator` that credits each sample from `rng` with `bits_per_sample` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{EntropyAccumulator, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let rng = SystemRng::new();
+    ///     let bits = rng.estimated_entropy_bits();
+    ///     let mut accumulator = EntropyAccumulator::new(rng, bits);
+    ///
+    ///     while !accumulator.is_ready(256.0) {
+    ///         accumulator.sample();
+    ///         println!("{}/256 bits", accumulator.accumulated_bits());
+    ///         sleep(EntropyAccumulator::<SystemRng>::SAMPLE_INTERVAL).await;
+    ///     }
+    ///     let seed = accumulator.finish();
+    /// }
+    /// ```
+    pub fn new(rng: R, bits_per_sample: f32) -> Self {
+        Self {
+            rng,
+            hasher: hasher(),
+            bits_per_sample,
+            accumulated_bits: 0.0,
+            sample_delay: Self::SAMPLE_INTERVAL,
+        }
+    }
+
+    /// Set the time [`gather`](Self::gather) and [`gather_blocking`](Self::gather_blocking) wait between samples.
+    ///
+    /// Sensor readings taken back to back are often identical, so the delay is what lets noise build up between samples.
+    /// It defaults to [`SAMPLE_INTERVAL`](Self::SAMPLE_INTERVAL).
+    /// A longer delay suits slow-changing sources like the battery; a zero delay samples as fast as the RNG allows.
+    #[must_use]
+    pub const fn with_sample_delay(mut self, delay: Duration) -> Self {
+        self.sample_delay = delay;
+        self
+    }
+
+    /// Returns the time waited between samples by [`gather`](Self::gather) and [`gather_blocking`](Self::gather_blocking).
+    pub const fn sample_delay(&self) -> Duration {
+        self.sample_delay
+    }
+
+    /// Draw a single sample from the inner RNG without waiting.
+    pub fn sample(&mut self) {
+        self.hasher.write_u64(self.rng.next_u64());
+        self.accumulated_bits += self.bits_per_sample;
+    }
+
+    /// Returns the estimated number of bits of entropy gathered so far.
+    pub const fn accumulated_bits(&self) -> f32 {
+        self.accumulated_bits
+    }
+
+    /// Returns `true` once at least `target` bits of entropy have been gathered.
+    pub fn is_ready(&self, target: f32) -> bool {
+        self.accumulated_bits >= target
+    }
+
+    /// Sample the inner RNG once per [sample delay](Self::with_sample_delay) until at least `target` bits have been gathered.
+    pub async fn gather(&mut self, target: f32) {
+        while !self.is_ready(target) {
+            self.sample();
+            sleep(self.sample_delay).await;
+        }
+    }
+
+    /// Like [`gather`](Self::gather), but spin between samples instead of yielding to other tasks.
+    ///
+    /// This is for code that can't await, like a constructor. It blocks for the whole gather,
+    /// so prefer `gather` once the async runtime is running.
+    pub fn gather_blocking(&mut self, target: f32) {
+        while !self.is_ready(target) {
+            self.sample();
+            let start = Instant::now();
+            while start.elapsed() < self.sample_delay {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Squeeze a 256-bit seed out of everything gathered so far.
+    pub fn finish(self) -> [u8; 32] {
+        squeeze_256(&self.hasher)
+    }
+
+    /// Like [`finish`](Self::finish), but wrap the seed in a [`Secret`] so it can't be logged by accident.
+    #[cfg(feature = "crypto")]
+    pub fn finish_secret(self) -> Secret<[u8; 32]> {
+        Secret::new(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use rand::RngCore;
+
+    use super::EntropyAccumulator;
+    use crate::{backend::mock, SystemRng};
+
+    /// An RNG that tracks the shortest time between two of its draws, without reading the clock itself.
+    #[derive(Default)]
+    struct Clocked {
+        last: Option<u64>,
+        shortest_gap: Option<u64>,
+    }
+
+    impl RngCore for Clocked {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let now = mock::with(|mock| mock.time).unwrap();
+            if let Some(last) = self.last {
+                let gap = now - last;
+                self.shortest_gap =
+                    Some(self.shortest_gap.map_or(gap, |shortest| shortest.min(gap)));
+            }
+            self.last = Some(now);
+            now
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let len = chunk.len();
+                chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn samples_accumulate_until_the_target() {
+        let mut accumulator = EntropyAccumulator::new(SystemRng::new(), 6.0);
+        let mut samples = 0;
+        while !accumulator.is_ready(64.0) {
+            let before = accumulator.accumulated_bits();
+            accumulator.sample();
+            assert!(accumulator.accumulated_bits() > before);
+            samples += 1;
+        }
+        assert_eq!(samples, 11);
+        assert!(accumulator.accumulated_bits() >= 64.0);
+    }
+
+    #[test]
+    fn blocking_gathers_wait_the_sample_delay_between_samples() {
+        mock::install(mock::Mock {
+            time_step: 100,
+            ..mock::Mock::frozen()
+        });
+        let mut spaced = EntropyAccumulator::new(Clocked::default(), 8.0)
+            .with_sample_delay(Duration::from_millis(1));
+        spaced.gather_blocking(64.0);
+        let gap = spaced.rng.shortest_gap.unwrap();
+        assert!((1000..1300).contains(&gap), "{gap}");
+
+        let mut unspaced =
+            EntropyAccumulator::new(Clocked::default(), 8.0).with_sample_delay(Duration::ZERO);
+        unspaced.gather_blocking(64.0);
+        assert!(unspaced.rng.shortest_gap.unwrap() < 1000);
+    }
+}
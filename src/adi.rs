@@ -0,0 +1,233 @@
+use core::{fmt, hash::Hasher, time::Duration};
+
+use vexide_core::float::Float;
+use vexide_devices::adi::AdiPort;
+
+use crate::backend::{self, Instant};
+
+/// How often VEXos refreshes ADI readings.
+pub(crate) const REFRESH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The Smart Port number VEXos uses for the brain's onboard ADI ports.
+pub(crate) const ONBOARD_EXPANDER_NUMBER: u8 = 22;
+
+/// An error returned when an [`AdiRng`](crate::AdiRng) is configured incorrectly.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum AdiRngError {
+    /// The same port was passed more than once, which would double-count it in the entropy estimate.
+    DuplicatePort {
+        /// The Smart Port number of the port's expander, or `22` for the brain's onboard ports.
+        expander_number: u8,
+        /// The number of the port on its expander.
+        number: u8,
+    },
+}
+
+impl fmt::Display for AdiRngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicatePort {
+                expander_number,
+                number,
+            } => write!(
+                f,
+                "ADI port {number} on expander {expander_number} was passed more than once"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for AdiRngError {}
+
+/// Returns the expander and port number that identify `port`.
+pub(crate) fn port_id(port: &AdiPort) -> (u8, u8) {
+    (
+        port.expander_number().unwrap_or(ONBOARD_EXPANDER_NUMBER),
+        port.number(),
+    )
+}
+
+/// Returns an estimate of the entropy, in bits, of each reading of `port`, based on its spread over `samples` readings.
+///
+/// The readings are treated as Gaussian noise, whose entropy per sample is `log2(2πe σ²) / 2` bits.
+/// Each reading is taken after waiting for the ADI readings to refresh, so this blocks for `samples` refresh intervals.
+/// A port whose reading never changes is reported as contributing `0.0` bits.
+pub(crate) fn port_entropy(port: &AdiPort, samples: usize) -> f32 {
+    let (mut sum, mut sum_squares) = (0.0, 0.0);
+    for _ in 0..samples {
+        let start = Instant::now();
+        while start.elapsed() < REFRESH_INTERVAL {
+            core::hint::spin_loop();
+        }
+
+        let x = f64::from(backend::adi_value(port));
+        sum += x;
+        sum_squares += x * x;
+    }
+
+    let n = samples as f64;
+    let variance = (sum_squares - sum * sum / n) / n;
+    if samples == 0 || variance <= 0.0 {
+        return 0.0;
+    }
+    let bits = Float::log2(2.0 * core::f64::consts::PI * core::f64::consts::E * variance) / 2.0;
+    bits.clamp(0.0, 12.0) as f32
+}
+
+/// Returns the Pearson correlation of `samples` paired readings of `a` and `b`, from `-1.0` to `1.0`.
+///
+/// Each pair is read after waiting for the ADI readings to refresh, so this blocks for `samples` refresh intervals.
+/// If either port's reading never changes, the correlation is reported as `0.0`.
+pub(crate) fn correlation(a: &AdiPort, b: &AdiPort, samples: usize) -> f32 {
+    let (mut sum_a, mut sum_b) = (0.0, 0.0);
+    let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0, 0.0, 0.0);
+    for _ in 0..samples {
+        let start = Instant::now();
+        while start.elapsed() < REFRESH_INTERVAL {
+            core::hint::spin_loop();
+        }
+
+        let x = f64::from(backend::adi_value(a));
+        let y = f64::from(backend::adi_value(b));
+        sum_a += x;
+        sum_b += y;
+        sum_aa += x * x;
+        sum_bb += y * y;
+        sum_ab += x * y;
+    }
+
+    let n = samples as f64;
+    let covariance = n * sum_ab - sum_a * sum_b;
+    let variance = (n * sum_aa - sum_a * sum_a) * (n * sum_bb - sum_b * sum_b);
+    if variance <= 0.0 {
+        return 0.0;
+    }
+    (covariance / Float::sqrt(variance)) as f32
+}
+
+/// Write the expander and port number of `port` into `hasher`.
+///
+/// This separates the contributions of ports that share a port number on different expanders,
+/// so that two physically different ports can never be mistaken for each other in the hash.
+pub(crate) fn write_port_id(hasher: &mut impl Hasher, port: &AdiPort) {
+    let (expander_number, number) = port_id(port);
+    hasher.write_u8(expander_number);
+    hasher.write_u8(number);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+
+    use rand::RngCore;
+    use vexide_devices::adi::AdiPort;
+
+    use super::{port_id, AdiRngError, ONBOARD_EXPANDER_NUMBER};
+    use crate::{backend::mock, AdiRng};
+
+    /// Returns port A on the brain and port A on the expander in Smart Port 3.
+    fn overlapping_ports() -> [AdiPort; 2] {
+        // SAFETY: The ports never reach the SDK; the mock backend only reads their numbers.
+        unsafe { [AdiPort::new(1, None), AdiPort::new(1, Some(3))] }
+    }
+
+    #[test]
+    fn expanders_tag_ports_with_the_same_number() {
+        let [onboard, expander] = overlapping_ports();
+        assert_eq!(port_id(&onboard), (ONBOARD_EXPANDER_NUMBER, 1));
+        assert_eq!(port_id(&expander), (3, 1));
+    }
+
+    #[test]
+    fn ports_with_the_same_number_contribute_differently() {
+        mock::install(mock::Mock::frozen());
+        mock::with(|mock| mock.adi = Some(Box::new(|_| 100)));
+        let ports = overlapping_ports();
+        let onboard = AdiRng::new(&ports[..1]).next_u64();
+        let expander = AdiRng::new(&ports[1..]).next_u64();
+        assert_ne!(onboard, expander);
+        assert_eq!(AdiRng::new(&ports[..1]).next_u64(), onboard);
+    }
+
+    #[test]
+    fn try_new_rejects_duplicate_ports() {
+        // SAFETY: The ports never reach the SDK; the mock backend only reads their numbers.
+        let duplicates = unsafe {
+            [
+                AdiPort::new(1, None),
+                AdiPort::new(2, None),
+                AdiPort::new(1, None),
+            ]
+        };
+        assert_eq!(
+            AdiRng::try_new(&duplicates).err(),
+            Some(AdiRngError::DuplicatePort {
+                expander_number: ONBOARD_EXPANDER_NUMBER,
+                number: 1,
+            })
+        );
+        assert!(AdiRng::try_new(&overlapping_ports()).is_ok());
+        assert!(AdiRng::try_new(&mock::ports::<3>()).is_ok());
+    }
+
+    /// Returns a 12-bit reading from a xorshift generator, standing in for a floating port.
+    fn noise(state: &mut u64) -> i32 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state >> 52) as i32
+    }
+
+    /// Install ADI readings drawn from [`noise`], where port B reads the same value as port A if `coupled`.
+    fn install_noise(coupled: bool) {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut last = 0;
+        mock::install(mock::Mock {
+            time_step: 1000,
+            adi: Some(Box::new(move |port| {
+                if !coupled || port.number() == 1 {
+                    last = noise(&mut state);
+                }
+                last
+            })),
+            ..mock::Mock::frozen()
+        });
+    }
+
+    #[test]
+    fn coupled_ports_are_correlated() {
+        let ports = mock::ports::<2>();
+        install_noise(true);
+        assert!(AdiRng::new(&ports).port_correlation(200) > 0.99);
+        install_noise(false);
+        assert!(AdiRng::new(&ports).port_correlation(200) < 0.2);
+        assert_eq!(AdiRng::new(&ports[..1]).port_correlation(200), 0.0);
+    }
+
+    #[test]
+    fn a_stuck_port_reports_no_entropy() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        mock::install(mock::Mock {
+            time_step: 1000,
+            adi: Some(Box::new(move |port| {
+                if port.number() == 2 {
+                    return 2048;
+                }
+                noise(&mut state)
+            })),
+            ..mock::Mock::frozen()
+        });
+        let ports = mock::ports::<3>();
+        let mut rng = AdiRng::new(&ports);
+        let report: std::vec::Vec<(u8, f32)> = rng.entropy_per_port(64).collect();
+        assert_eq!(report.len(), 3);
+        for (number, bits) in report {
+            if number == 2 {
+                assert_eq!(bits, 0.0);
+            } else {
+                assert!(bits > 4.0, "port {number}: {bits}");
+            }
+        }
+    }
+}
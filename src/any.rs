@@ -0,0 +1,111 @@
+use rand::RngCore;
+
+use crate::{AdiEdgeSource, AdiRng, CompositeRng, JitterRng, SystemRng};
+
+/// One of veranda's hardware RNGs, chosen at runtime.
+///
+/// This lets a program store whichever RNG it configured in a single field without boxing a trait object,
+/// which isn't possible without `alloc`. Every [`RngCore`] method dispatches to the active variant.
+#[derive(Debug, Eq, PartialEq)]
+pub enum AnyRng<'a> {
+    /// A [`SystemRng`].
+    System(SystemRng),
+    /// An [`AdiRng`].
+    Adi(AdiRng<'a>),
+    /// A [`CompositeRng`] sampling the edge timing of a digital ADI input.
+    AdiEdge(CompositeRng<AdiEdgeSource<'a>>),
+    /// A [`JitterRng`].
+    Jitter(JitterRng),
+}
+
+impl RngCore for AnyRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::System(rng) => rng.next_u32(),
+            Self::Adi(rng) => rng.next_u32(),
+            Self::AdiEdge(rng) => rng.next_u32(),
+            Self::Jitter(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::System(rng) => rng.next_u64(),
+            Self::Adi(rng) => rng.next_u64(),
+            Self::AdiEdge(rng) => rng.next_u64(),
+            Self::Jitter(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::System(rng) => rng.fill_bytes(dest),
+            Self::Adi(rng) => rng.fill_bytes(dest),
+            Self::AdiEdge(rng) => rng.fill_bytes(dest),
+            Self::Jitter(rng) => rng.fill_bytes(dest),
+        }
+    }
+}
+
+impl From<SystemRng> for AnyRng<'_> {
+    fn from(rng: SystemRng) -> Self {
+        Self::System(rng)
+    }
+}
+
+impl From<JitterRng> for AnyRng<'_> {
+    fn from(rng: JitterRng) -> Self {
+        Self::Jitter(rng)
+    }
+}
+
+impl<'a> From<AdiRng<'a>> for AnyRng<'a> {
+    fn from(rng: AdiRng<'a>) -> Self {
+        Self::Adi(rng)
+    }
+}
+
+impl<'a> From<CompositeRng<AdiEdgeSource<'a>>> for AnyRng<'a> {
+    fn from(rng: CompositeRng<AdiEdgeSource<'a>>) -> Self {
+        Self::AdiEdge(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::AnyRng;
+    use crate::{backend::mock, AdiEdgeSource, AdiRng, CompositeRng, JitterRng, SystemRng};
+
+    /// Returns the first draw of the RNG made by `make`, from a freshly installed mock,
+    /// so that two RNGs built the same way read exactly the same values.
+    fn first_draw<R: RngCore>(make: impl FnOnce() -> R) -> u64 {
+        mock::install(mock::Mock {
+            time_step: 1000,
+            ..mock::Mock::frozen()
+        });
+        make().next_u64()
+    }
+
+    #[test]
+    fn every_variant_dispatches_to_its_rng() {
+        let ports = mock::ports::<2>();
+        assert_eq!(
+            first_draw(|| AnyRng::from(SystemRng::new())),
+            first_draw(SystemRng::new)
+        );
+        assert_eq!(
+            first_draw(|| AnyRng::from(AdiRng::new(&ports))),
+            first_draw(|| AdiRng::new(&ports))
+        );
+        assert_eq!(
+            first_draw(|| AnyRng::from(CompositeRng::new(AdiEdgeSource::new(&ports[0])))),
+            first_draw(|| CompositeRng::new(AdiEdgeSource::new(&ports[0])))
+        );
+        assert_eq!(
+            first_draw(|| AnyRng::from(JitterRng::new())),
+            first_draw(JitterRng::new)
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! A stand-in for the VEXos SDK on computers.
+//!
+//! The timers are backed by the system clock, so output still varies between draws and between runs.
+//! There is no battery or ADI hardware, so those readings are always zero.
+//! In unit tests, any of the readings can be replaced through [`mock`].
+
+use std::{
+    sync::OnceLock,
+    time::{self, SystemTime},
+};
+
+use vexide_devices::adi::AdiPort;
+
+/// Returns the time since the program started, in microseconds.
+pub(crate) fn high_res_time() -> u64 {
+    #[cfg(test)]
+    if let Some(time) = mock::with(mock::Mock::read_time) {
+        return time;
+    }
+    static START: OnceLock<time::Instant> = OnceLock::new();
+    START.get_or_init(time::Instant::now).elapsed().as_micros() as u64
+}
+
+/// Returns the time since the Unix epoch, in microseconds.
+///
+/// This stands in for the brain's powerup time, since it differs between runs.
+pub(crate) fn powerup_time() -> u64 {
+    #[cfg(test)]
+    if let Some(time) = mock::with(|mock| {
+        mock.calls.powerup_time += 1;
+        mock.powerup_time
+    }) {
+        return time;
+    }
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Always returns zero, since there is no field controller.
+pub(crate) fn competition_status() -> u32 {
+    #[cfg(test)]
+    return mock::with(|mock| {
+        mock.calls.competition_status += 1;
+        mock.competition_status
+    });
+    #[cfg(not(test))]
+    0
+}
+
+/// Always returns `None`, since there is no brain to identify.
+pub(crate) fn device_identity() -> Option<u64> {
+    #[cfg(test)]
+    return mock::with(|mock| mock.identity);
+    #[cfg(not(test))]
+    None
+}
+
+/// Always returns zero, since there is no battery.
+#[cfg_attr(not(feature = "battery"), allow(dead_code))]
+pub(crate) fn battery_voltage() -> f64 {
+    #[cfg(test)]
+    return mock::with(|mock| {
+        mock.calls.battery_voltage += 1;
+        mock.battery.0
+    });
+    #[cfg(not(test))]
+    0.0
+}
+
+/// Always returns zero, since there is no battery.
+pub(crate) fn battery_current() -> f64 {
+    #[cfg(test)]
+    return mock::with(|mock| {
+        mock.calls.battery_current += 1;
+        mock.current
+            .as_mut()
+            .map_or(mock.battery.1, |current| current())
+    });
+    #[cfg(not(test))]
+    0.0
+}
+
+/// Always returns zero, since there are no ADI ports.
+pub(crate) fn adi_value(_port: &AdiPort) -> i32 {
+    #[cfg(test)]
+    return mock::with(|mock| {
+        mock.calls.adi_value += 1;
+        mock.adi.as_mut().map_or(0, |adi| adi(_port))
+    });
+    #[cfg(not(test))]
+    0
+}
+
+/// Does nothing, since there are no ADI ports.
+pub(crate) fn adi_configure_digital_in(_port: &AdiPort) {}
+
+/// Replaceable readings for unit tests, with a count of how often each one was taken.
+///
+/// The mock is kept per thread, and every test runs on its own thread,
+/// so each test starts from [`Mock::default`] and its changes never leak into another test.
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::{boxed::Box, cell::RefCell};
+
+    use vexide_devices::adi::AdiPort;
+
+    /// The number of times each backend function has been called on this thread.
+    #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+    pub(crate) struct Calls {
+        pub(crate) high_res_time: u64,
+        pub(crate) powerup_time: u64,
+        pub(crate) battery_voltage: u64,
+        pub(crate) battery_current: u64,
+        pub(crate) adi_value: u64,
+        pub(crate) competition_status: u64,
+    }
+
+    impl Calls {
+        /// Returns the number of calls to every hardware reading, not counting the timers.
+        pub(crate) fn hardware_reads(&self) -> u64 {
+            self.battery_voltage + self.battery_current + self.adi_value
+        }
+    }
+
+    /// Produces the raw value of an ADI port.
+    pub(crate) type AdiReading = Box<dyn FnMut(&AdiPort) -> i32>;
+
+    /// The readings returned by the backend on this thread.
+    ///
+    /// By default, the timers follow the system clock and everything else reads zero, like the plain stub.
+    #[derive(Default)]
+    pub(crate) struct Mock {
+        /// A fixed reading of the high-resolution timer, in microseconds, instead of the system clock.
+        pub(crate) time: Option<u64>,
+        /// How far the fixed high-resolution timer advances after every read, in microseconds.
+        ///
+        /// Code that waits for time to pass would hang on a frozen timer, so this lets it make progress
+        /// while the readings stay reproducible.
+        pub(crate) time_step: u64,
+        /// Returns how far the fixed high-resolution timer advances after each read, instead of `time_step`.
+        pub(crate) time_steps: Option<Box<dyn FnMut() -> u64>>,
+        /// A fixed powerup time, in microseconds, instead of the system clock.
+        pub(crate) powerup_time: Option<u64>,
+        /// The battery voltage and current.
+        pub(crate) battery: (f64, f64),
+        /// Returns each reading of the battery current. Without it, every reading is the current in `battery`.
+        pub(crate) current: Option<Box<dyn FnMut() -> f64>>,
+        /// Returns the raw value of an ADI port. Without it, every port reads zero.
+        pub(crate) adi: Option<AdiReading>,
+        /// The brain's identity.
+        pub(crate) identity: Option<u64>,
+        /// The competition status bit field.
+        pub(crate) competition_status: u32,
+        /// The calls made so far.
+        pub(crate) calls: Calls,
+    }
+
+    impl Mock {
+        /// Returns a mock whose timers never advance, so every reading is the same on every draw.
+        pub(crate) fn frozen() -> Self {
+            Self {
+                time: Some(1_000_000),
+                powerup_time: Some(5_000_000),
+                ..Self::default()
+            }
+        }
+
+        /// Count a read of the high-resolution timer, returning the fixed reading if there is one.
+        pub(super) fn read_time(&mut self) -> Option<u64> {
+            self.calls.high_res_time += 1;
+            let time = self.time?;
+            let step = self
+                .time_steps
+                .as_mut()
+                .map_or(self.time_step, |step| step());
+            self.time = Some(time + step);
+            Some(time)
+        }
+    }
+
+    std::thread_local! {
+        static MOCK: RefCell<Mock> = RefCell::new(Mock::default());
+    }
+
+    /// Replace the mock for this thread, resetting the call counts.
+    pub(crate) fn install(mock: Mock) {
+        MOCK.set(mock);
+    }
+
+    /// Run `f` with the mock for this thread.
+    pub(crate) fn with<R>(f: impl FnOnce(&mut Mock) -> R) -> R {
+        MOCK.with_borrow_mut(f)
+    }
+
+    /// Returns the calls made so far on this thread.
+    pub(crate) fn calls() -> Calls {
+        with(|mock| mock.calls)
+    }
+
+    /// Returns `N` distinct onboard ADI ports, starting from port A.
+    pub(crate) fn ports<const N: usize>() -> [AdiPort; N] {
+        // SAFETY: The ports never reach the SDK; the mock backend only reads their numbers.
+        core::array::from_fn(|index| unsafe { AdiPort::new(index as u8 + 1, None) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{high_res_time, mock, powerup_time};
+
+    #[test]
+    fn frozen_mock_advances_by_its_step_and_counts_reads() {
+        mock::install(mock::Mock {
+            time_step: 10,
+            ..mock::Mock::frozen()
+        });
+        assert_eq!(high_res_time(), 1_000_000);
+        assert_eq!(high_res_time(), 1_000_010);
+        assert_eq!(powerup_time(), powerup_time());
+        let calls = mock::calls();
+        assert_eq!((calls.high_res_time, calls.powerup_time), (2, 2));
+        assert_eq!(calls.hardware_reads(), 0);
+    }
+}
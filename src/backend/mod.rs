@@ -0,0 +1,38 @@
+//! Access to the hardware sampled by the RNGs.
+//!
+//! On the brain, every reading comes from the VEXos SDK. With the `host` feature, the SDK is replaced by
+//! a stub built on `std`, so that programs using veranda can be run and tested on a computer.
+//! The unit tests always use the stub, with its readings overridden by the mock in [`host::mock`].
+
+use core::time::Duration;
+
+#[cfg(any(test, feature = "host"))]
+mod host;
+#[cfg(not(any(test, feature = "host")))]
+mod vex;
+
+#[cfg(any(test, feature = "host"))]
+pub(crate) use host::*;
+#[cfg(not(any(test, feature = "host")))]
+pub(crate) use vex::*;
+
+/// A measurement of the high-resolution timer, with a precision of 1 microsecond.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub(crate) struct Instant(u64);
+
+impl Instant {
+    /// The instant the high-resolution timer started at.
+    pub(crate) const ZERO: Self = Self(0);
+
+    pub(crate) fn now() -> Self {
+        Self(high_res_time())
+    }
+
+    pub(crate) fn duration_since(&self, earlier: Instant) -> Duration {
+        Duration::from_micros(self.0.saturating_sub(earlier.0))
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+}
@@ -0,0 +1,70 @@
+use vex_sdk::{
+    vexCompetitionStatus, vexDeviceAdiPortConfigSet, vexDeviceAdiValueGet, vexDeviceGetByIndex,
+    vexSystemHighResTimeGet, vexSystemPowerupTimeGet, V5_AdiPortConfiguration, V5_DeviceT,
+};
+use vexide_devices::{adi::AdiPort, battery};
+
+/// Returns the time since the program started, in microseconds.
+pub(crate) fn high_res_time() -> u64 {
+    unsafe { vexSystemHighResTimeGet() }
+}
+
+/// Returns the time since the brain was powered on, in microseconds.
+pub(crate) fn powerup_time() -> u64 {
+    unsafe { vexSystemPowerupTimeGet() }
+}
+
+/// Returns the competition status reported by the field controller, as a bit field.
+///
+/// VEXos doesn't expose the field's match timer, so this is the closest thing to a shared field clock.
+pub(crate) fn competition_status() -> u32 {
+    unsafe { vexCompetitionStatus() }
+}
+
+/// Returns a value unique to this brain, if VEXos exposes one.
+///
+/// The SDK doesn't currently provide the brain's serial number or any other per-device identity,
+/// so this always returns `None`.
+pub(crate) fn device_identity() -> Option<u64> {
+    None
+}
+
+/// Returns the battery voltage, in volts.
+#[cfg_attr(not(feature = "battery"), allow(dead_code))]
+pub(crate) fn battery_voltage() -> f64 {
+    battery::voltage()
+}
+
+/// Returns the current drawn from the battery, in amps.
+pub(crate) fn battery_current() -> f64 {
+    battery::current()
+}
+
+/// Returns the device handle of the ADI expander (or onboard ADI) that `port` belongs to.
+fn adi_device(port: &AdiPort) -> V5_DeviceT {
+    let smart_port = port
+        .expander_number()
+        .unwrap_or(crate::adi::ONBOARD_EXPANDER_NUMBER);
+    unsafe { vexDeviceGetByIndex(u32::from(smart_port - 1)) }
+}
+
+/// Returns the index of `port` on its device.
+fn adi_index(port: &AdiPort) -> u32 {
+    u32::from(port.number() - 1)
+}
+
+/// Returns the current raw value of `port`.
+pub(crate) fn adi_value(port: &AdiPort) -> i32 {
+    unsafe { vexDeviceAdiValueGet(adi_device(port), adi_index(port)) }
+}
+
+/// Configure `port` as a digital input.
+pub(crate) fn adi_configure_digital_in(port: &AdiPort) {
+    unsafe {
+        vexDeviceAdiPortConfigSet(
+            adi_device(port),
+            adi_index(port),
+            V5_AdiPortConfiguration::kAdiPortTypeDigitalIn,
+        );
+    }
+}
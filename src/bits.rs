@@ -0,0 +1,68 @@
+use rand::RngCore;
+
+/// Serves random bits one at a time from a borrowed RNG.
+///
+/// A full `u64` is drawn and handed out bit by bit, so only one draw is made for every 64 bits consumed.
+/// Every bit of a draw is used exactly once, so the bits are as unbiased and independent as the RNG's output.
+#[derive(Debug)]
+pub struct RandomBits<'a, R: ?Sized> {
+    rng: &'a mut R,
+    bits: u64,
+    remaining: u8,
+}
+impl<'a, R: RngCore + ?Sized> RandomBits<'a, R> {
+    /// Create a new `RandomBits` that draws from `rng` whenever it runs out of bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{RandomBits, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let mut bits = RandomBits::new(&mut rng);
+    ///     if bits.next_bit() {
+    ///         println!("Heads");
+    ///     } else {
+    ///         println!("Tails");
+    ///     }
+    /// }
+    /// ```
+    pub fn new(rng: &'a mut R) -> Self {
+        Self {
+            rng,
+            bits: 0,
+            remaining: 0,
+        }
+    }
+
+    /// Returns the next random bit, drawing a new word from the RNG if the current one is used up.
+    pub fn next_bit(&mut self) -> bool {
+        if self.remaining == 0 {
+            self.bits = self.rng.next_u64();
+            self.remaining = 64;
+        }
+        let bit = self.bits & 1 == 1;
+        self.bits >>= 1;
+        self.remaining -= 1;
+        bit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomBits;
+    use crate::SystemRng;
+
+    #[test]
+    fn every_64_bits_take_one_draw() {
+        let mut rng = SystemRng::new();
+        let start = rng.draw_count();
+        let mut bits = RandomBits::new(&mut rng);
+        let stream: [bool; 128] = core::array::from_fn(|_| bits.next_bit());
+        assert_eq!(rng.draw_count() - start, 2);
+        assert!(stream.contains(&true) && stream.contains(&false));
+    }
+}
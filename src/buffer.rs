@@ -0,0 +1,44 @@
+use core::fmt;
+
+/// Leftover bytes from a previous hash that haven't been handed out yet.
+///
+/// This lets narrow values like `u8`s share a single hash instead of each one re-sampling hardware.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub(crate) struct ByteBuffer {
+    bytes: [u8; 8],
+    remaining: u8,
+}
+
+impl ByteBuffer {
+    pub(crate) const fn new() -> Self {
+        Self {
+            bytes: [0; 8],
+            remaining: 0,
+        }
+    }
+
+    /// Take the next unused byte, or `None` if the buffer needs to be refilled.
+    pub(crate) fn pop(&mut self) -> Option<u8> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let byte = self.bytes[self.bytes.len() - self.remaining as usize];
+        self.remaining -= 1;
+        Some(byte)
+    }
+
+    /// Replace the contents of the buffer with the bytes of a fresh hash.
+    pub(crate) fn refill(&mut self, value: u64) {
+        self.bytes = value.to_le_bytes();
+        self.remaining = self.bytes.len() as u8;
+    }
+}
+
+/// The bytes are output that hasn't been handed out yet, so only how many are left is shown.
+impl fmt::Debug for ByteBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteBuffer")
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,68 @@
+use vexide_devices::adi::AdiPort;
+
+use crate::{
+    health::{self, InitError},
+    AdiRng, SystemRng, XorRng,
+};
+
+/// The recommended hardware RNG for programs with at least one free ADI port:
+/// a [`SystemRng`] with every safe extra input, XORed with an [`AdiRng`] over the free ports.
+///
+/// Construct one with [`CombinedRng::standard`].
+pub type CombinedRng<'a> = XorRng<SystemRng, AdiRng<'a>>;
+
+impl<'a> XorRng<SystemRng, AdiRng<'a>> {
+    /// Create the recommended hardware RNG from the given empty ADI ports, running the startup self-test.
+    ///
+    /// The `SystemRng` half is configured like [`SystemRng::max_entropy`], and the `AdiRng` half samples every port in `ports`.
+    /// This is the best choice for most programs that have a free port. Programs without one should use
+    /// [`SystemRng::max_entropy`] instead.
+    ///
+    /// # Errors
+    ///
+    /// - An [`InitError::SelfTestFailed`] error is returned if the system metrics never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::CombinedRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let Peripherals { adi_g, adi_h, .. } = p;
+    ///     let ports = [adi_g, adi_h];
+    ///     let mut rng = CombinedRng::standard(&ports).expect("entropy sources have collapsed");
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub fn standard(ports: &'a [AdiPort]) -> Result<Self, InitError> {
+        let mut system = SystemRng::max_entropy();
+        if health::inputs_stuck(&mut system.state) {
+            return Err(InitError::SelfTestFailed);
+        }
+        Ok(XorRng::new(system, AdiRng::new(ports)))
+    }
+}
+
+// The deterministic readings replace the frozen mock, so the timers never stall.
+#[cfg(all(test, not(feature = "deterministic")))]
+mod tests {
+    use crate::{
+        backend::mock::{self, ports},
+        health::InitError,
+        CombinedRng,
+    };
+
+    #[test]
+    fn standard_refuses_a_stuck_backend() {
+        let ports = ports::<2>();
+        assert!(CombinedRng::standard(&ports).is_ok());
+        mock::install(mock::Mock::frozen());
+        assert_eq!(
+            CombinedRng::standard(&ports).err(),
+            Some(InitError::SelfTestFailed)
+        );
+    }
+}
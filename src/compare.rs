@@ -0,0 +1,52 @@
+/// Compare two byte slices, such as generated tokens, in time that only depends on their length.
+///
+/// A plain `==` returns at the first differing byte, so how long it takes reveals how much of a guess was right.
+/// This instead visits every byte of both slices before answering.
+/// Slices of different lengths are unequal, and that is decided up front, so lengths shouldn't be secret.
+///
+/// Constant-time execution is best-effort: the comparison is written to avoid data-dependent branches,
+/// but neither the compiler nor the brain's processor guarantees it, so don't rely on it against a determined attacker.
+///
+/// # Examples
+///
+/// ```
+/// use veranda::timing_safe_equal;
+///
+/// let token = [0x12, 0x34, 0x56, 0x78];
+/// assert!(timing_safe_equal(&token, &[0x12, 0x34, 0x56, 0x78]));
+/// assert!(!timing_safe_equal(&token, &[0x12, 0x34, 0x56, 0x79]));
+/// assert!(!timing_safe_equal(&token, &[0x12, 0x34]));
+/// ```
+pub fn timing_safe_equal(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a
+        .iter()
+        .zip(b)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    // Hide the result from the optimizer so it has no reason to specialize the loop around it.
+    core::hint::black_box(difference) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::timing_safe_equal;
+
+    #[test]
+    fn only_identical_slices_are_equal() {
+        let token = [0x5a; 16];
+        assert!(timing_safe_equal(&token, &[0x5a; 16]));
+        assert!(timing_safe_equal(&[], &[]));
+        for index in 0..token.len() {
+            for bit in 0..8 {
+                let mut guess = token;
+                guess[index] ^= 1 << bit;
+                assert!(!timing_safe_equal(&token, &guess), "{index}, {bit}");
+            }
+        }
+        assert!(!timing_safe_equal(&token, &token[..15]));
+        assert!(!timing_safe_equal(&token[..1], &token));
+        assert!(!timing_safe_equal(&token, &[]));
+    }
+}
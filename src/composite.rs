@@ -0,0 +1,134 @@
+use core::hash::Hasher;
+
+use ahash::AHasher;
+use rand::{distr::Distribution, RngCore};
+
+#[cfg(feature = "crypto")]
+use crate::Secret;
+use crate::{hasher, source::EntropySource, squeeze_256, squeeze_distinct, state::State};
+
+/// A [`rand`](https://crates.io/crates/rand) RNG source that mixes one or more [`EntropySource`]s into the system metrics used by [`SystemRng`](crate::SystemRng).
+///
+/// Multiple sources can be combined by passing a tuple or array of sources.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompositeRng<S> {
+    source: S,
+    state: State,
+}
+impl<S: EntropySource> CompositeRng<S> {
+    /// Create a new `CompositeRng` that samples `source` on every draw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{AdiEdgeSource, CompositeRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let mut rng = CompositeRng::new(AdiEdgeSource::new(&p.adi_h));
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            state: State::new(),
+        }
+    }
+
+    /// Returns the inner source.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+
+    /// Permanently mix the output of another RNG into the state of this one.
+    ///
+    /// A word is drawn from `other` and folded into the stored seed, so every future draw depends on both RNGs.
+    pub fn merge(&mut self, other: &mut impl RngCore) {
+        let value = other.next_u64();
+        self.state.mix_seed(|hasher| hasher.write_u64(value));
+    }
+
+    /// Fold 32 bytes drawn from `source` into the stored seed.
+    ///
+    /// This is a heavier version of [`merge`](Self::merge) for injecting externally gathered randomness.
+    /// In tests, seeding from a deterministic RNG pins down every part of the state that isn't read from hardware.
+    pub fn seed_from_rng(&mut self, source: &mut impl RngCore) {
+        let mut seed = [0; 32];
+        source.fill_bytes(&mut seed);
+        self.state.mix_seed(|hasher| hasher.write(&seed));
+    }
+
+    /// Discard all accumulated state and start over as if this RNG had just been created.
+    ///
+    /// The creation instant is re-anchored to now, the stored seed (including anything added by [`merge`](Self::merge))
+    /// is zeroed, the counter is reset, and a warm-up draw is made and discarded.
+    /// Use this after a suspected entropy compromise, when stirring in more material isn't enough.
+    pub fn reset_to_fresh(&mut self) {
+        self.state.reset();
+        self.hash_value();
+    }
+
+    /// Sample every entropy source once and squeeze a 256-bit seed out of the result.
+    ///
+    /// This is more efficient than four separate draws when seeding a 256-bit RNG,
+    /// since the hardware is only sampled once. The seed is produced by re-hashing the accumulated
+    /// state with an incrementing counter, so its four words are distinct.
+    /// Keep in mind that the seed contains no more entropy than a single draw.
+    pub fn finalize_256(&mut self) -> [u8; 32] {
+        squeeze_256(&self.accumulate())
+    }
+
+    /// Sample every entropy source once and squeeze `N` distinct 64-bit seeds out of the result.
+    ///
+    /// This is meant for seeding one small PRNG per task, like [`SmallRng`](rand::rngs::SmallRng), from a single hardware sample.
+    /// The seeds are guaranteed to be pairwise distinct, but like [`finalize_256`](Self::finalize_256),
+    /// together they contain no more entropy than a single draw.
+    pub fn seed_array<const N: usize>(&mut self) -> [u64; N] {
+        squeeze_distinct(&self.accumulate())
+    }
+
+    /// Like [`finalize_256`](Self::finalize_256), but wrap the seed in a [`Secret`](crate::Secret) so it can't be logged by accident.
+    #[cfg(feature = "crypto")]
+    pub fn finalize_256_secret(&mut self) -> Secret<[u8; 32]> {
+        Secret::new(self.finalize_256())
+    }
+
+    /// Draw a value from `distribution` using this RNG.
+    ///
+    /// This is the same as [`Rng::sample`](rand::Rng::sample), but doesn't require importing [`rand::Rng`].
+    pub fn sample<T, D: Distribution<T>>(&mut self, distribution: D) -> T {
+        distribution.sample(self)
+    }
+
+    fn accumulate(&mut self) -> AHasher {
+        let mut hasher = hasher();
+        self.source.sample(&mut hasher);
+        self.state.write_inputs(&mut hasher);
+        hasher
+    }
+
+    fn hash_value(&mut self) -> u64 {
+        self.accumulate().finish()
+    }
+}
+
+impl<S: EntropySource> RngCore for CompositeRng<S> {
+    fn next_u32(&mut self) -> u32 {
+        self.hash_value() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.hash_value()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let value = self.hash_value();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
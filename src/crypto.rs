@@ -0,0 +1,248 @@
+use core::hash::Hasher;
+
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use zeroize::Zeroize;
+
+use crate::{hasher, health::FillError, squeeze_256};
+
+/// A ChaCha20 generator keyed from a hardware RNG, with conservative entropy accounting.
+///
+/// Every hardware sample credits the generator's entropy budget with a fixed number of bits,
+/// and every byte of output debits it by eight bits. [`try_fill_bytes`](Self::try_fill_bytes)
+/// refuses to emit more bytes than the budget covers until [`reseed`](Self::reseed) credits more,
+/// so crypto-grade output is never stretched further than the entropy gathered for it.
+/// The budget is capped at 256 bits, the size of the ChaCha20 key.
+///
+/// The infallible [`RngCore`] methods still debit the budget, but never fail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CryptoVeranda<R> {
+    source: R,
+    bits_per_sample: f32,
+    remaining_bits: f32,
+    chacha: ChaCha20Rng,
+}
+impl<R: RngCore> CryptoVeranda<R> {
+    /// The most entropy, in bits, that the budget can hold.
+    pub const MAX_ENTROPY_BITS: f32 = 256.0;
+
+    /// Create a new `CryptoVeranda` that credits each sample from `source` with `bits_per_sample` bits.
+    ///
+    /// The generator is keyed from a single sample, so it starts out with a budget of `bits_per_sample` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{CryptoVeranda, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let source = SystemRng::new();
+    ///     let bits = source.estimated_entropy_bits();
+    ///     let mut rng = CryptoVeranda::new(source, bits);
+    ///
+    ///     let mut key = [0; 16];
+    ///     while rng.remaining_entropy_bits() < 128.0 {
+    ///         rng.reseed();
+    ///     }
+    ///     rng.try_fill_bytes(&mut key).unwrap();
+    /// }
+    /// ```
+    pub fn new(source: R, bits_per_sample: f32) -> Self {
+        let mut rng = Self {
+            source,
+            bits_per_sample,
+            remaining_bits: 0.0,
+            chacha: ChaCha20Rng::from_seed([0; 32]),
+        };
+        rng.reseed();
+        rng
+    }
+
+    /// Returns the hardware RNG this generator is keyed from.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /// Returns the estimated entropy, in bits, that has been gathered but not yet spent on output.
+    pub fn remaining_entropy_bits(&self) -> f32 {
+        self.remaining_bits
+    }
+
+    /// Draw a fresh hardware sample and rekey the generator with it, crediting the budget.
+    ///
+    /// The new key depends on both the sample and the previous key, so reseeding never loses entropy.
+    /// The copies of the previous keystream and the new key are zeroized once the new key is installed.
+    pub fn reseed(&mut self) {
+        let mut previous = [0; 32];
+        self.chacha.fill_bytes(&mut previous);
+
+        let mut hasher = hasher();
+        hasher.write(&previous);
+        hasher.write_u64(self.source.next_u64());
+        let mut seed = squeeze_256(&hasher);
+        self.chacha = ChaCha20Rng::from_seed(seed);
+        seed.zeroize();
+        previous.zeroize();
+
+        self.remaining_bits =
+            (self.remaining_bits + self.bits_per_sample).min(Self::MAX_ENTROPY_BITS);
+    }
+
+    /// Ratchet the ChaCha20 key forward without touching the hardware or the entropy budget.
+    ///
+    /// The next 32 bytes of keystream become the new key. The old generator state,
+    /// including its key and any buffered keystream, is overwritten with zeros before the new key is installed,
+    /// and the copy of the new key is zeroized. Output produced before the rekey can't be recovered from the state after it,
+    /// so call this right after generating long-term key material, before the generator is used for anything else.
+    /// Unlike [`reseed`](Self::reseed), this adds no entropy.
+    pub fn rekey(&mut self) {
+        let mut key = [0; 32];
+        self.chacha.fill_bytes(&mut key);
+        wipe(&mut self.chacha);
+        self.chacha = ChaCha20Rng::from_seed(key);
+        key.zeroize();
+    }
+
+    /// Fill `dest` with random bytes, failing if the entropy budget doesn't cover them.
+    ///
+    /// Each byte costs eight bits of the budget. Nothing is written or debited if the fill fails.
+    ///
+    /// # Errors
+    ///
+    /// - A [`FillError::EntropyExhausted`] error is returned if `dest` needs more bits than
+    ///   [`remaining_entropy_bits`](Self::remaining_entropy_bits).
+    pub fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), FillError> {
+        let cost = dest.len() as f32 * 8.0;
+        if cost > self.remaining_bits {
+            return Err(FillError::EntropyExhausted);
+        }
+        self.fill_bytes(dest);
+        Ok(())
+    }
+
+    fn debit(&mut self, bytes: usize) {
+        self.remaining_bits = (self.remaining_bits - bytes as f32 * 8.0).max(0.0);
+    }
+}
+
+impl<R: RngCore> RngCore for CryptoVeranda<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.debit(4);
+        self.chacha.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.debit(8);
+        self.chacha.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.debit(dest.len());
+        self.chacha.fill_bytes(dest);
+    }
+}
+
+impl<R: RngCore> CryptoRng for CryptoVeranda<R> {}
+
+/// Overwrite a ChaCha20 generator with one keyed from zeros.
+///
+/// `ChaCha20Rng` doesn't implement `Zeroize`, and a plain assignment may be optimized out
+/// if the old value is never read again, so this writes volatilely.
+fn wipe(chacha: &mut ChaCha20Rng) {
+    // SAFETY: `chacha` is a valid, aligned, exclusive reference, and `ChaCha20Rng` has no drop glue to skip.
+    unsafe { core::ptr::write_volatile(chacha, ChaCha20Rng::from_seed([0; 32])) };
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Expand a 256-bit hardware seed into `N` bytes of key material with ChaCha20, then zeroize the seed.
+pub(crate) fn expand_key<const N: usize>(mut seed: [u8; 32]) -> [u8; N] {
+    let mut chacha = ChaCha20Rng::from_seed(seed);
+    seed.zeroize();
+    let mut key = [0; N];
+    chacha.fill_bytes(&mut key);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::{wipe, CryptoVeranda};
+    use crate::{health::FillError, SystemRng};
+
+    #[test]
+    fn emitting_beyond_the_budget_fails_until_a_reseed() {
+        let mut rng = CryptoVeranda::new(SystemRng::new(), 64.0);
+        let mut block = [0; 8];
+
+        rng.try_fill_bytes(&mut block).unwrap();
+        assert_eq!(rng.remaining_entropy_bits(), 0.0);
+        assert_eq!(
+            rng.try_fill_bytes(&mut block),
+            Err(FillError::EntropyExhausted)
+        );
+
+        rng.reseed();
+        rng.try_fill_bytes(&mut block).unwrap();
+        assert_eq!(
+            rng.try_fill_bytes(&mut block),
+            Err(FillError::EntropyExhausted)
+        );
+    }
+
+    #[test]
+    fn a_failed_fill_writes_nothing() {
+        let mut rng = CryptoVeranda::new(SystemRng::new(), 64.0);
+        let mut block = [0; 16];
+        assert_eq!(
+            rng.try_fill_bytes(&mut block),
+            Err(FillError::EntropyExhausted)
+        );
+        assert_eq!(block, [0; 16]);
+        assert_eq!(rng.remaining_entropy_bits(), 64.0);
+    }
+
+    #[test]
+    fn the_budget_is_capped() {
+        let mut rng = CryptoVeranda::new(SystemRng::new(), 200.0);
+        rng.reseed();
+        assert_eq!(
+            rng.remaining_entropy_bits(),
+            CryptoVeranda::<SystemRng>::MAX_ENTROPY_BITS
+        );
+    }
+
+    #[test]
+    fn rekey_output_is_unrelated_to_the_old_stream() {
+        let mut rng = CryptoVeranda::new(SystemRng::new(), 64.0);
+        let mut before = rng.clone();
+        let old_seed = rng.chacha.get_seed();
+
+        rng.rekey();
+        assert_ne!(rng.chacha.get_seed(), old_seed);
+
+        // The new key is the next block of the old stream, so the output after it must not repeat
+        // anything the old generator would have produced.
+        let mut old_stream = [0; 256];
+        before.chacha.fill_bytes(&mut old_stream);
+        let mut new_block = [0; 32];
+        rng.chacha.fill_bytes(&mut new_block);
+        assert!(old_stream.windows(32).all(|window| window != new_block));
+        assert_eq!(
+            rng.remaining_entropy_bits(),
+            before.remaining_entropy_bits()
+        );
+    }
+
+    #[test]
+    fn wipe_zeroes_the_old_key() {
+        let mut chacha = <ChaCha20Rng as rand::SeedableRng>::from_seed([7; 32]);
+        chacha.next_u32();
+        wipe(&mut chacha);
+        assert_eq!(chacha.get_seed(), [0; 32]);
+        assert_eq!(chacha.get_word_pos(), 0);
+    }
+}
@@ -0,0 +1,118 @@
+use core::hash::Hasher;
+
+use crate::{backend, mix, source::EntropySource};
+
+/// An [`EntropySource`] that reads the battery current several times in quick succession and mixes its jitter.
+///
+/// While motors are running, PWM switching makes the current drawn from the battery fluctuate constantly,
+/// which is a much richer source than the near-constant current of an idle robot.
+/// Each sample mixes the change between consecutive readings, in millionths of an amp, along with their variance.
+/// This is most useful during a match; with the motors idle, it contributes much less.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CurrentJitterSource {
+    readings: usize,
+}
+impl CurrentJitterSource {
+    /// The default number of readings taken per sample.
+    pub const DEFAULT_READINGS: usize = 8;
+
+    /// Create a new `CurrentJitterSource` that takes [`DEFAULT_READINGS`](Self::DEFAULT_READINGS) readings per sample.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{CompositeRng, CurrentJitterSource};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = CompositeRng::new(CurrentJitterSource::new());
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            readings: Self::DEFAULT_READINGS,
+        }
+    }
+
+    /// Set how many readings are taken per sample.
+    ///
+    /// More readings capture more jitter, but make each draw slower.
+    pub const fn with_readings(mut self, readings: usize) -> Self {
+        self.readings = readings;
+        self
+    }
+}
+
+impl Default for CurrentJitterSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntropySource for CurrentJitterSource {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        let mut last = backend::battery_current();
+        let mut sum = 0.0;
+        let mut sum_of_squares = 0.0;
+        for _ in 1..self.readings {
+            let current = backend::battery_current();
+            let delta = current - last;
+            mix::write_float(hasher, delta * 1000.0);
+            sum += delta;
+            sum_of_squares += delta * delta;
+            last = current;
+        }
+
+        let deltas = self.readings.saturating_sub(1).max(1) as f64;
+        let mean = sum / deltas;
+        mix::write_float(
+            hasher,
+            (sum_of_squares / deltas - mean * mean) * 1_000_000.0,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use std::boxed::Box;
+
+    use super::CurrentJitterSource;
+    use crate::{backend::mock, hasher, source::EntropySource};
+
+    /// Sample a source while the battery current follows `current`.
+    fn sampled(current: impl FnMut() -> f64 + 'static) -> u64 {
+        mock::install(mock::Mock {
+            current: Some(Box::new(current)),
+            ..mock::Mock::default()
+        });
+        let mut hasher = hasher();
+        CurrentJitterSource::new().sample(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a current that jitters above `base` in steps of about a thousandth of an amp, following `pattern`.
+    ///
+    /// The steps are powers of two, so the changes come out exactly the same for any `base`.
+    fn jittering(base: f64, pattern: u32) -> impl FnMut() -> f64 {
+        let mut reading = 0;
+        move || {
+            reading += 1;
+            base + f64::from(reading * pattern % 7) / 1024.0
+        }
+    }
+
+    #[test]
+    fn only_the_jitter_is_mixed() {
+        let steady = sampled(|| 1.5);
+        assert_eq!(sampled(|| 2.5), steady);
+        let jitter = sampled(jittering(1.5, 3));
+        assert_ne!(jitter, steady);
+        assert_eq!(sampled(jittering(2.5, 3)), jitter);
+        assert_ne!(sampled(jittering(1.5, 5)), jitter);
+    }
+}
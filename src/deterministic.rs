@@ -0,0 +1,383 @@
+//! Reproducible system readings for tests, and the known-answer vectors they make possible.
+//!
+//! Faking the readings needs the `deterministic` feature, but the vectors are checked by [`verify_self`] in every build.
+
+use core::hash::Hasher;
+#[cfg(feature = "deterministic")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use rand::RngCore;
+
+use crate::{
+    hasher,
+    mix::{derive_subseed, mix64, MixFn, SequentialHash, XorOfHashes},
+    replay::RawSample,
+    squeeze_256,
+    state::State,
+    Whitened,
+};
+
+/// The seed that every deterministic reading is derived from.
+#[cfg(feature = "deterministic")]
+static GLOBAL_TEST_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Set the seed that every RNG's system readings are derived from while the `deterministic` feature is enabled.
+///
+/// With the feature on, RNGs built on the system metrics ([`SystemRng`](crate::SystemRng), [`AdiRng`](crate::AdiRng),
+/// and [`CompositeRng`](crate::CompositeRng)) never read the timers or the battery.
+/// Every reading is instead generated from this seed and the RNG's draw counter,
+/// so two RNGs configured the same way produce identical streams, no matter when they are created.
+/// The seed is `0` until this is called.
+///
+/// One-time inputs that don't come from the system metrics, like
+/// [`with_address_entropy`](crate::SystemRng::with_address_entropy), still vary and should be left off in tests.
+#[cfg(feature = "deterministic")]
+pub fn set_global_test_seed(seed: u64) {
+    GLOBAL_TEST_SEED.store(seed, Ordering::Relaxed);
+}
+
+/// Replace each enabled system reading with one generated from the global test seed and `counter`.
+#[cfg(feature = "deterministic")]
+pub(crate) fn readings(
+    counter: u64,
+    battery: Option<(f64, f64)>,
+    program_time: Option<u128>,
+    powerup_time: Option<u64>,
+) -> (Option<(f64, f64)>, Option<u128>, Option<u64>) {
+    readings_from(
+        GLOBAL_TEST_SEED.load(Ordering::Relaxed),
+        counter,
+        battery,
+        program_time,
+        powerup_time,
+    )
+}
+
+/// Replace each enabled system reading with one generated from `seed` and `counter`.
+fn readings_from(
+    seed: u64,
+    counter: u64,
+    battery: Option<(f64, f64)>,
+    program_time: Option<u128>,
+    powerup_time: Option<u64>,
+) -> (Option<(f64, f64)>, Option<u128>, Option<u64>) {
+    let mut hasher = hasher();
+    hasher.write_u64(seed);
+    hasher.write_u64(counter);
+    let value = hasher.finish();
+
+    // Keep the timers advancing with the counter, so that they are never considered stuck.
+    (
+        battery.map(|_| {
+            (
+                f64::from(value as u16) / 1000.0,
+                f64::from((value >> 16) as u16) / 1000.0,
+            )
+        }),
+        program_time.map(|_| u128::from(counter) * 1000 + u128::from(value >> 54)),
+        powerup_time.map(|_| value),
+    )
+}
+
+/// The test seed and RNG seed that the known-answer vectors are generated from.
+const VECTOR_SEED: u64 = 0x7665_7261_6e64_6121;
+
+/// The number of draws checked for each mixing strategy.
+const VECTOR_DRAWS: usize = 4;
+
+/// The numbers of inner draws hashed into each known-answer [`Whitened`] output, one adapter for each.
+const VECTOR_WHITEN_KS: [usize; 2] = [2, 4];
+
+/// The number of outputs checked from each [`Whitened`] adapter.
+const VECTOR_WHITENED: usize = 2;
+
+/// The draws for both mixing strategies, the four words of a 256-bit seed, a [`mix64`] of the seed,
+/// the four words of a [`derive_subseed`] from the seed, and the outputs of each [`Whitened`] adapter.
+const VECTOR_OUTPUTS: usize =
+    2 * VECTOR_DRAWS + 4 + 1 + 4 + VECTOR_WHITEN_KS.len() * VECTOR_WHITENED;
+
+/// The label the known-answer subseed is derived with.
+const VECTOR_LABEL: &[u8] = b"veranda";
+
+/// The inner RNG of the known-answer [`Whitened`] outputs, which counts up from its starting value.
+struct Counter(u64);
+
+impl RngCore for Counter {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.0;
+        self.0 = self.0.wrapping_add(1);
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..len]);
+        }
+    }
+}
+
+/// The expected output of [`vector_outputs`], for each of the ways ahash can hash.
+///
+/// ahash switches to AES instructions on x86 when they are enabled at compile time,
+/// and shuffles their input differently depending on whether SSSE3 is enabled too.
+/// Without AES, it only uses a 128-bit multiply on some 64-bit targets.
+/// Each of those changes every hash. The brain always takes the last, portable path.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    target_feature = "ssse3",
+    not(miri)
+))]
+const EXPECTED: [u64; VECTOR_OUTPUTS] = [
+    0xc1bd2ac152f40e4c,
+    0x67f4af480124c839,
+    0x394c7188e1b6eae8,
+    0x1fbf262285f94a5d,
+    0x30394e2abb91e589,
+    0xc2d902c5cd860702,
+    0xde3bce19ce8f9783,
+    0xe9da622f4c0b4c5d,
+    0xdc8f212b50c63615,
+    0xd6f3d2cd4786b414,
+    0x753c4253be1dc61b,
+    0x5af43b430b3ffb61,
+    0xedb22d55fb0507dd,
+    0x5281f07f0dcb977e,
+    0x36c5ca28c2098fc1,
+    0x4509e2708dd0df0b,
+    0xcfc63599b7db36c8,
+    0x4a0a9c8d6adf9f9b,
+    0x6817b0914af10c72,
+    0x15a4b56837ea216e,
+    0x4e5ec4909682fcfd,
+];
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    not(target_feature = "ssse3"),
+    not(miri)
+))]
+const EXPECTED: [u64; VECTOR_OUTPUTS] = [
+    0x9774df1a30497a1c,
+    0x39d20aee1b9ff5dc,
+    0x4f7dbb4a842b77fb,
+    0xd2432ad87f920137,
+    0x13afb8313427c411,
+    0x6b11ab8d24e2e19f,
+    0xb0ca09e39d8942e0,
+    0xd88e524a7ed9951d,
+    0xb91340c9a6a5799f,
+    0x1e8ede038a6414ae,
+    0x1b12b7f3eefcd225,
+    0x5a164da7561b75e6,
+    0xab38a31325ae6de0,
+    0xaa39461cf9933c9d,
+    0x50f1db97d6db3631,
+    0x2fb5ad00156ea6ac,
+    0x733a4cf08407e534,
+    0xa4dc7b7ac48857a6,
+    0x2032e8a6116d7262,
+    0x6f473c305568e165,
+    0xab3fe710031731fc,
+];
+#[cfg(all(
+    not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(miri)
+    )),
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "mips64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )
+))]
+const EXPECTED: [u64; VECTOR_OUTPUTS] = [
+    0x098e00814cfe9a26,
+    0xa2060969a7d62d3f,
+    0x17cfa4a42272f8d4,
+    0x925e8d39ace2b93d,
+    0x040eb121a8b16682,
+    0xc5da04452be801ab,
+    0x39668b096819c993,
+    0x49d1902708b1465e,
+    0xb6210cd7b624cf08,
+    0x08ac0f775ed351dc,
+    0x44fa7690966636e0,
+    0xf56ee92493dbdab1,
+    0x5d04328ab22f312f,
+    0xf716c4288f533bd2,
+    0x36ad7272ec1f7a81,
+    0xfe64522e4399a563,
+    0xfcd10b74a3a28148,
+    0x786f7ec19b867325,
+    0xf028ff94e455005a,
+    0xe7d27c8283481e0c,
+    0xfb1d1f834f175c67,
+];
+#[cfg(all(
+    not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(miri)
+    )),
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "mips64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    ))
+))]
+const EXPECTED: [u64; VECTOR_OUTPUTS] = [
+    0x99c1477e9f39a2a1,
+    0x3a9a840120f5400a,
+    0x074cf7470bb8d6fe,
+    0xf0ce9cf3ff5239ab,
+    0x9e15a17ad9599b65,
+    0x6bfcf4d63f55e892,
+    0xfc334bc894f6f043,
+    0xa50d23d2dd4eb43f,
+    0x7ea1f30898d9c5f0,
+    0xa5330afa939cea32,
+    0x00f6998eae1efe6a,
+    0x1d0d82b9e9eac960,
+    0xe187626484485c43,
+    0x5c9ab64fe9c69906,
+    0x87a74bea9274856d,
+    0xc20ca3787e679b94,
+    0x8d5f5392bd53e8ec,
+    0x28b9dcee928bb3c3,
+    0x2a64c42fd66d859d,
+    0xcd7734bf5bad6266,
+    0xff7c7db7a53b2445,
+];
+
+/// Run the mixing pipeline over deterministic readings: a few draws with each mixing strategy,
+/// followed by a 256-bit seed squeezed from the last draw, the public mixing and derivation functions over that seed,
+/// and [`Whitened`] adapters over a counter for each of [`VECTOR_WHITEN_KS`].
+fn vector_outputs() -> [u64; VECTOR_OUTPUTS] {
+    let mut outputs = [0; VECTOR_OUTPUTS];
+    let mut last = hasher();
+    for (round, mix) in [MixFn::of::<SequentialHash>(), MixFn::of::<XorOfHashes>()]
+        .into_iter()
+        .enumerate()
+    {
+        let mut state = State::uninit();
+        state.seed = VECTOR_SEED;
+        state.mix = mix;
+        for draw in 0..VECTOR_DRAWS {
+            let (battery, program_time, powerup_time) = readings_from(
+                VECTOR_SEED,
+                state.counter,
+                Some((0.0, 0.0)),
+                Some(0),
+                Some(0),
+            );
+            let sample = RawSample {
+                battery,
+                program_time,
+                powerup_time,
+            };
+            let mut hasher = hasher();
+            state.write_sample(&mut hasher, &sample);
+            outputs[round * VECTOR_DRAWS + draw] = hasher.finish();
+            last = hasher;
+        }
+    }
+    let seed = squeeze_256(&last);
+    for (output, chunk) in outputs[2 * VECTOR_DRAWS..]
+        .iter_mut()
+        .zip(seed.chunks_exact(8))
+    {
+        *output = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    outputs[2 * VECTOR_DRAWS + 4] = mix64(&outputs[2 * VECTOR_DRAWS..2 * VECTOR_DRAWS + 4]);
+    for (output, chunk) in outputs[2 * VECTOR_DRAWS + 5..]
+        .iter_mut()
+        .zip(derive_subseed(&seed, VECTOR_LABEL).chunks_exact(8))
+    {
+        *output = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    let whitened_start = VECTOR_OUTPUTS - VECTOR_WHITEN_KS.len() * VECTOR_WHITENED;
+    for (chunk, k) in outputs[whitened_start..]
+        .chunks_exact_mut(VECTOR_WHITENED)
+        .zip(VECTOR_WHITEN_KS)
+    {
+        let mut whitened = Whitened::new(Counter(VECTOR_SEED), k);
+        for output in chunk {
+            *output = whitened.next_u64();
+        }
+    }
+    outputs
+}
+
+/// Check that the mixing pipeline reproduces veranda's known-answer vectors.
+///
+/// This is a self-test for the build rather than for the hardware: it runs draws over readings generated
+/// from a fixed seed, through every mixing strategy, the 256-bit seed expansion, [`mix64`](crate::mix64),
+/// [`derive_subseed`](crate::derive_subseed), and [`Whitened`](crate::Whitened),
+/// and compares the output to values pinned by this version of veranda.
+/// A `false` result means the build hashes differently than it was released with, for example because
+/// of a miscompilation or a changed dependency, and any recorded seeds or test vectors can't be trusted.
+///
+/// It runs on the brain as well as on a computer, and never reads the hardware.
+/// It uses its own seed, so it doesn't disturb the `deterministic` feature's global test seed or any RNG.
+///
+/// # Examples
+///
+/// ```
+/// assert!(veranda::verify_self(), "veranda failed its self-test");
+/// ```
+pub fn verify_self() -> bool {
+    vector_outputs() == EXPECTED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{readings_from, vector_outputs, EXPECTED};
+
+    #[test]
+    fn vectors_match_this_build() {
+        assert_eq!(vector_outputs(), EXPECTED);
+        assert!(super::verify_self());
+    }
+
+    #[test]
+    fn readings_depend_on_the_seed_and_counter() {
+        let reading =
+            |seed, counter| readings_from(seed, counter, Some((0.0, 0.0)), Some(0), Some(0));
+        assert_eq!(reading(1, 5), reading(1, 5));
+        assert_ne!(reading(2, 5), reading(1, 5));
+        assert_ne!(reading(1, 6), reading(1, 5));
+        assert_eq!(readings_from(1, 5, None, None, Some(0)).0, None);
+    }
+
+    // Tests run in parallel, so the global seed is left at its default rather than changed here.
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn rngs_with_the_same_global_seed_are_identical() {
+        use core::time::Duration;
+
+        use rand::RngCore;
+
+        use crate::{backend::mock, AdiRng, SystemRng};
+
+        let ports = mock::ports::<2>();
+        let streams = || {
+            let mut system = SystemRng::new();
+            let mut adi = AdiRng::new(&ports);
+            core::array::from_fn::<_, 4, _>(|_| (system.next_u64(), adi.next_u64()))
+        };
+        let first = streams();
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(streams(), first);
+    }
+}
@@ -0,0 +1,208 @@
+//! On-device diagnostics for measuring the behavior of an RNG configuration.
+
+use core::{fmt, time::Duration};
+
+use rand::RngCore;
+use vexide_core::{float::Float, print};
+
+use crate::{backend::Instant, health::EntropyStatus, VerandaRngExt};
+
+/// How long the benchmark in a startup report runs for.
+const STARTUP_BENCHMARK_DURATION: Duration = Duration::from_millis(100);
+
+/// The results of a [`benchmark`](crate::VerandaRngExt::benchmark) run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkReport {
+    /// The number of values drawn during the benchmark.
+    pub draws: u64,
+    /// The number of drawn values that did not repeat a recently drawn value.
+    pub distinct: u64,
+    /// The time the benchmark actually ran for, as measured by the high-resolution timer.
+    pub elapsed: Duration,
+}
+
+impl BenchmarkReport {
+    /// Returns the number of values drawn per second.
+    pub fn draws_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.draws as f64 / seconds
+    }
+
+    /// Returns the fraction of draws that were distinct from recent output, from `0.0` to `1.0`.
+    ///
+    /// A healthy RNG should report a ratio of `1.0` or extremely close to it.
+    /// A ratio well below `1.0` means that the RNG is getting stuck on the same values.
+    pub fn distinct_ratio(&self) -> f64 {
+        if self.draws == 0 {
+            return 0.0;
+        }
+        self.distinct as f64 / self.draws as f64
+    }
+}
+
+/// The largest monobit statistic that passes, corresponding to a p-value of `0.01`.
+const MONOBIT_LIMIT: f64 = 2.575_829_3;
+
+/// The largest runs statistic that passes, corresponding to a p-value of `0.01`.
+const RUNS_LIMIT: f64 = 1.821_386_4;
+
+/// The results of a [`quick_statistical_test`](crate::VerandaRngExt::quick_statistical_test) run.
+///
+/// Both tests follow NIST SP 800-22 and pass when their p-value is at least `0.01`.
+/// Rather than computing p-values, each statistic is compared against the equivalent critical value,
+/// so a smaller statistic is always better.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestOutcome {
+    /// The number of bits tested.
+    pub bits: u64,
+    /// The number of tested bits that were set.
+    pub ones: u64,
+    /// The number of runs of identical bits in the tested stream.
+    pub runs: u64,
+    /// The normalized difference between the number of ones and zeros.
+    pub monobit_statistic: f64,
+    /// The normalized difference between the observed and expected number of runs.
+    ///
+    /// This is infinite if the stream was too biased for the runs test to apply,
+    /// in which case the monobit test has failed as well.
+    pub runs_statistic: f64,
+}
+
+impl TestOutcome {
+    pub(crate) fn new(bits: u64, ones: u64, runs: u64) -> Self {
+        if bits == 0 {
+            return Self {
+                bits,
+                ones,
+                runs,
+                monobit_statistic: f64::INFINITY,
+                runs_statistic: f64::INFINITY,
+            };
+        }
+
+        let n = bits as f64;
+        let sum = 2.0 * ones as f64 - n;
+        let monobit_statistic = Float::abs(sum) / Float::sqrt(n);
+
+        let proportion = ones as f64 / n;
+        let runs_statistic = if Float::abs(proportion - 0.5) >= 2.0 / Float::sqrt(n) {
+            f64::INFINITY
+        } else {
+            let spread = proportion * (1.0 - proportion);
+            Float::abs(runs as f64 - 2.0 * n * spread) / (2.0 * Float::sqrt(2.0 * n) * spread)
+        };
+
+        Self {
+            bits,
+            ones,
+            runs,
+            monobit_statistic,
+            runs_statistic,
+        }
+    }
+
+    /// Returns `true` if the tested bits had a plausible balance of ones and zeros.
+    pub fn monobit_passed(&self) -> bool {
+        self.monobit_statistic <= MONOBIT_LIMIT
+    }
+
+    /// Returns `true` if the tested bits switched between ones and zeros a plausible number of times.
+    pub fn runs_passed(&self) -> bool {
+        self.runs_statistic <= RUNS_LIMIT
+    }
+
+    /// Returns `true` if both tests passed.
+    ///
+    /// Even a perfect RNG fails each test about 1% of the time, so a single failure isn't conclusive,
+    /// but repeated failures mean that the RNG is almost certainly broken.
+    pub fn passed(&self) -> bool {
+        self.monobit_passed() && self.runs_passed()
+    }
+}
+
+/// The number of degrees of freedom of the chi-square statistic over byte values.
+const BYTE_DEGREES_OF_FREEDOM: f64 = 255.0;
+
+/// Score the output quality of `rng` over `samples` draws, where higher is better and a perfect RNG scores about `1.0`.
+///
+/// The score is the fraction of draws distinct from the 32 before them, minus two penalties that are each
+/// capped at `1.0`: the monobit statistic relative to its passing limit, and the chi-square statistic's
+/// deviation from its expected value, in standard deviations, relative to the same limit.
+/// Both penalties stay well below `1.0` for a healthy RNG, so the score is dominated by obvious failures.
+pub(crate) fn quality_score(rng: &mut (impl RngCore + ?Sized), samples: usize) -> f64 {
+    let mut recent = [0; 32];
+    let mut distinct = 0;
+    for draw in 0..samples {
+        let value = rng.next_u64();
+        if !recent[..draw.min(recent.len())].contains(&value) {
+            distinct += 1;
+        }
+        recent[draw % recent.len()] = value;
+    }
+    let distinct_ratio = if samples == 0 {
+        0.0
+    } else {
+        distinct as f64 / samples as f64
+    };
+
+    let monobit_penalty = rng.quick_statistical_test(samples).monobit_statistic / MONOBIT_LIMIT;
+    let chi_square = rng.chi_square_bytes(samples * 8);
+    let chi_square_penalty = Float::abs(chi_square - BYTE_DEGREES_OF_FREEDOM)
+        / Float::sqrt(2.0 * BYTE_DEGREES_OF_FREEDOM)
+        / MONOBIT_LIMIT;
+
+    distinct_ratio - monobit_penalty.min(1.0) - chi_square_penalty.min(1.0)
+}
+
+/// The console, as a [`fmt::Write`] sink for reports.
+pub(crate) struct Console;
+
+impl fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        print!("{s}");
+        Ok(())
+    }
+}
+
+/// Write a startup report for `rng` to `out`, using a `status` taken before any draws.
+///
+/// `self_test` runs the RNG's startup self-test, returning `true` if it passed.
+/// `sources` describes the inputs the RNG mixes into each draw.
+pub(crate) fn write_startup_report<R: RngCore>(
+    out: &mut impl fmt::Write,
+    rng: &mut R,
+    self_test: impl FnOnce(&mut R) -> bool,
+    sources: fmt::Arguments<'_>,
+    status: EntropyStatus,
+) -> fmt::Result {
+    let start = Instant::now();
+    let self_test_passed = self_test(rng);
+    let benchmark = rng.benchmark(STARTUP_BENCHMARK_DURATION);
+
+    writeln!(out, "veranda startup report")?;
+    writeln!(out, "  sources: {sources}")?;
+    writeln!(
+        out,
+        "  estimated entropy: {:.1} bits per draw",
+        status.estimated_bits
+    )?;
+    match status.warning {
+        Some(warning) => writeln!(out, "  health: {warning:?}")?,
+        None => writeln!(out, "  health: ok")?,
+    }
+    writeln!(
+        out,
+        "  self-test: {}",
+        if self_test_passed { "passed" } else { "failed" }
+    )?;
+    writeln!(
+        out,
+        "  benchmark: {:.0} draws/sec, {:.1}% distinct",
+        benchmark.draws_per_second(),
+        benchmark.distinct_ratio() * 100.0
+    )?;
+    writeln!(out, "  time taken: {:?}", start.elapsed())
+}
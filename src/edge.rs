@@ -0,0 +1,127 @@
+use core::{hash::Hasher, time::Duration};
+
+use vexide_devices::adi::AdiPort;
+
+use crate::{
+    backend::{self, Instant},
+    source::EntropySource,
+};
+
+/// An [`EntropySource`] that times the transitions of an empty ADI port configured as a digital input.
+///
+/// A floating digital input flips between high and low unpredictably.
+/// Each sample polls the port for a short window and mixes the time between every transition it sees,
+/// which captures more unpredictability than a single analog reading.
+///
+/// ADI values are only updated every 10 milliseconds, so shorter windows will often see no transitions at all.
+/// The default window of 20 milliseconds always spans at least one update; use [`with_window`](Self::with_window)
+/// to poll for longer if the port is mostly used for seeding and latency doesn't matter.
+///
+/// Just like with [`AdiRng`](crate::AdiRng), the port must not be connected to anything.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AdiEdgeSource<'a> {
+    port: &'a AdiPort,
+    window: Duration,
+}
+impl<'a> AdiEdgeSource<'a> {
+    /// The default length of time each sample polls the port for, two ADI update periods.
+    pub const DEFAULT_WINDOW: Duration = Duration::from_millis(20);
+
+    /// Create a new `AdiEdgeSource`, configuring `port` as a digital input.
+    pub fn new(port: &'a AdiPort) -> Self {
+        backend::adi_configure_digital_in(port);
+        Self {
+            port,
+            window: Self::DEFAULT_WINDOW,
+        }
+    }
+
+    /// Set how long each sample polls the port for transitions.
+    pub const fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+impl EntropySource for AdiEdgeSource<'_> {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        let start = Instant::now();
+        let mut last_edge = start;
+        let mut last_value = backend::adi_value(self.port);
+        let mut edges = 0u32;
+
+        while start.elapsed() < self.window {
+            let value = backend::adi_value(self.port);
+            if value != last_value {
+                let now = Instant::now();
+                hasher.write_u64(now.duration_since(last_edge).as_micros() as u64);
+                last_edge = now;
+                last_value = value;
+                edges += 1;
+            }
+        }
+
+        hasher.write_u32(edges);
+        hasher.write_i32(last_value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+    use std::{boxed::Box, vec::Vec};
+
+    use super::AdiEdgeSource;
+    use crate::{adi, backend::mock, source::EntropySource};
+
+    /// A hasher that records every `u64` written to it, which is how the source writes intervals.
+    #[derive(Default)]
+    struct Intervals(Vec<u64>);
+
+    impl Hasher for Intervals {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+
+        fn write_u64(&mut self, value: u64) {
+            self.0.push(value);
+        }
+    }
+
+    /// Sample a source over a port that flips every `period` reads, with the timer advancing 100µs per read.
+    fn intervals(period: u32) -> Vec<u64> {
+        mock::install(mock::Mock {
+            time_step: 100,
+            ..mock::Mock::frozen()
+        });
+        let mut reads = 0;
+        mock::with(|mock| {
+            mock.adi = Some(Box::new(move |_| {
+                reads += 1;
+                ((reads / period) % 2) as i32
+            }))
+        });
+
+        let ports = mock::ports::<1>();
+        let mut hasher = Intervals::default();
+        AdiEdgeSource::new(&ports[0]).sample(&mut hasher);
+        hasher.0
+    }
+
+    #[test]
+    fn edge_intervals_are_mixed() {
+        let fast = intervals(3);
+        let slow = intervals(6);
+        assert!(fast.len() > slow.len() && !slow.is_empty());
+        // Past the first edge, the source sees a steady toggle, so every interval matches.
+        assert!(fast[1..].iter().all(|&interval| interval == fast[1]));
+        assert!(slow[1] > fast[1]);
+    }
+
+    #[test]
+    fn the_default_window_spans_an_adi_update() {
+        assert!(AdiEdgeSource::DEFAULT_WINDOW >= adi::REFRESH_INTERVAL);
+    }
+}
@@ -0,0 +1,1319 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::{
+    cmp::Ordering,
+    future::Future,
+    num::NonZeroU64,
+    ops::RangeInclusive,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use rand::{Rng, RngCore};
+#[cfg(feature = "alloc")]
+use vexide_core::float::Float;
+
+use crate::{
+    backend::Instant,
+    diagnostics::{self, BenchmarkReport, TestOutcome},
+    FastRng, U32Rng,
+};
+
+/// The number of recent values a benchmark compares each draw against.
+const BENCHMARK_WINDOW: usize = 32;
+
+/// The number of bytes [`VerandaRngExt::fill_bytes_async`] fills between yields.
+const ASYNC_FILL_CHUNK_LEN: usize = 64;
+
+/// How many zero draws [`VerandaRngExt::next_nonzero_u64`] retries before giving up on the source.
+const MAX_NONZERO_RETRIES: usize = 16;
+
+/// The characters used by alphanumeric strings.
+const ALPHANUMERIC: &[u8; 62] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Extra functionality available on every [`RngCore`] implementor.
+pub trait VerandaRngExt: RngCore {
+    /// Draw as many values as possible for `duration` and report the throughput and distinctness of the output.
+    ///
+    /// Each draw is compared against the last 32 drawn values to determine whether it was distinct.
+    /// Timing is measured with the brain's high-resolution timer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::time::Duration;
+    ///
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let report = rng.benchmark(Duration::from_secs(1));
+    ///     println!("{} draws/sec", report.draws_per_second());
+    ///     println!("{} distinct", report.distinct_ratio());
+    /// }
+    /// ```
+    fn benchmark(&mut self, duration: Duration) -> BenchmarkReport {
+        let mut recent = [0; BENCHMARK_WINDOW];
+        let mut draws = 0u64;
+        let mut distinct = 0;
+
+        let start = Instant::now();
+        let mut elapsed = Duration::ZERO;
+        while elapsed < duration {
+            let value = self.next_u64();
+            let seen = (draws as usize).min(BENCHMARK_WINDOW);
+            if !recent[..seen].contains(&value) {
+                distinct += 1;
+            }
+            recent[draws as usize % BENCHMARK_WINDOW] = value;
+            draws += 1;
+            elapsed = start.elapsed();
+        }
+
+        BenchmarkReport {
+            draws,
+            distinct,
+            elapsed,
+        }
+    }
+
+    /// Run the NIST monobit and runs tests over the bits of `samples` draws.
+    ///
+    /// This is a quick on-device check that an RNG configuration isn't obviously broken, for example before a match.
+    /// It doesn't allocate, and only keeps a few counters while drawing.
+    /// With zero samples, both tests fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let outcome = rng.quick_statistical_test(1_000);
+    ///     if !outcome.passed() {
+    ///         println!("RNG failed its self-test: {outcome:?}");
+    ///     }
+    /// }
+    /// ```
+    fn quick_statistical_test(&mut self, samples: usize) -> TestOutcome {
+        let mut ones = 0;
+        let mut runs = 0;
+        let mut last_bit = None;
+        for _ in 0..samples {
+            let value = self.next_u64();
+            ones += u64::from(value.count_ones());
+
+            // Bits are tested from least to most significant, so a run can continue into the next word.
+            let transitions = (value ^ (value >> 1)) & (u64::MAX >> 1);
+            runs += u64::from(transitions.count_ones());
+            runs += match last_bit {
+                Some(bit) if bit == value & 1 => 0,
+                _ => 1,
+            };
+            last_bit = Some(value >> 63);
+        }
+        TestOutcome::new(samples as u64 * 64, ones, runs)
+    }
+
+    /// Draw `samples` values and report the fraction of them that had each of the 64 bits set.
+    ///
+    /// Index `i` of the report is the frequency of bit `i`, where bit `0` is the least significant.
+    /// Healthy output hovers near `0.5` at every position.
+    /// A position near `0.0` or `1.0` points to a stuck or strongly biased bit, which usually means a misconfigured source.
+    /// With zero samples, every frequency is reported as `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let report = rng.bit_frequency_report(10_000);
+    ///     for (bit, frequency) in report.iter().enumerate() {
+    ///         println!("bit {bit}: {frequency}");
+    ///     }
+    /// }
+    /// ```
+    fn bit_frequency_report(&mut self, samples: usize) -> [f32; 64] {
+        let mut ones = [0usize; 64];
+        for _ in 0..samples {
+            let value = self.next_u64();
+            for (bit, count) in ones.iter_mut().enumerate() {
+                *count += (value >> bit) as usize & 1;
+            }
+        }
+
+        let mut report = [0.0; 64];
+        if samples > 0 {
+            for (frequency, count) in report.iter_mut().zip(ones) {
+                *frequency = count as f32 / samples as f32;
+            }
+        }
+        report
+    }
+
+    /// Draw `samples` bytes and compute Pearson's chi-square statistic of their values against a uniform distribution.
+    ///
+    /// For uniform output, the statistic is close to its 255 degrees of freedom, and large values signal bias.
+    /// The spread depends on the sample size, so compare it to a threshold chosen for the number of samples drawn;
+    /// at a 1% significance level, that threshold is about `310.5`.
+    /// With zero samples, the statistic is reported as `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let statistic = rng.chi_square_bytes(25_600);
+    ///     if statistic > 310.5 {
+    ///         println!("byte output looks biased: {statistic}");
+    ///     }
+    /// }
+    /// ```
+    fn chi_square_bytes(&mut self, samples: usize) -> f64 {
+        let mut counts = [0usize; 256];
+        let mut remaining = samples;
+        while remaining > 0 {
+            let bytes = self.next_u64().to_le_bytes();
+            let take = remaining.min(bytes.len());
+            for &byte in &bytes[..take] {
+                counts[byte as usize] += 1;
+            }
+            remaining -= take;
+        }
+
+        if samples == 0 {
+            return 0.0;
+        }
+        let expected = samples as f64 / 256.0;
+        counts
+            .iter()
+            .map(|&count| {
+                let difference = count as f64 - expected;
+                difference * difference / expected
+            })
+            .sum()
+    }
+
+    /// Count how often each value of the low byte appears across `samples` draws.
+    ///
+    /// This is the data for a histogram, for example drawn as 256 bars on the brain's screen.
+    /// For uniform output the counts are all close to `samples / 256`, and a spike or a gap signals bias.
+    /// Counts saturate at [`u32::MAX`]. For a single number to compare against a threshold,
+    /// use [`chi_square_bytes`](Self::chi_square_bytes) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let histogram = rng.byte_histogram(25_600);
+    ///     let tallest = histogram.iter().max().unwrap();
+    ///     println!("The most common byte appeared {tallest} times, 100 were expected");
+    /// }
+    /// ```
+    fn byte_histogram(&mut self, samples: usize) -> [u32; 256] {
+        let mut counts = [0u32; 256];
+        for _ in 0..samples {
+            let byte = self.next_u32() as u8;
+            counts[byte as usize] = counts[byte as usize].saturating_add(1);
+        }
+        counts
+    }
+
+    /// Compare the measured output quality of this RNG against `other`, returning [`Ordering::Greater`] if this one is better.
+    ///
+    /// Both RNGs draw `samples` values for each of three checks, which are combined into a score:
+    /// the fraction of draws that didn't repeat a recent value, minus penalties for the
+    /// [monobit statistic](Self::quick_statistical_test) and for how far the [chi-square statistic](Self::chi_square_bytes)
+    /// is from its expected value, each measured against the 1% significance limit and capped.
+    /// A healthy RNG scores close to `1.0` and a stuck one close to `-1.0`,
+    /// so the comparison is meant for telling working configurations from broken ones,
+    /// not for ranking two healthy RNGs, which come out equal up to noise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::cmp::Ordering;
+    ///
+    /// use veranda::{AdiRng, SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let three_ports = [p.adi_a, p.adi_b, p.adi_c];
+    ///     let one_port = [p.adi_d];
+    ///     let mut a = AdiRng::new(&three_ports);
+    ///     let mut b = AdiRng::new(&one_port);
+    ///     if a.compare_quality(&mut b, 1_000) == Ordering::Less {
+    ///         println!("the single port looks better");
+    ///     }
+    /// }
+    /// ```
+    fn compare_quality(&mut self, other: &mut Self, samples: usize) -> Ordering
+    where
+        Self: Sized,
+    {
+        let ours = diagnostics::quality_score(self, samples);
+        let theirs = diagnostics::quality_score(other, samples);
+        ours.partial_cmp(&theirs).unwrap_or(Ordering::Equal)
+    }
+
+    /// Fill `dest` with random bytes, processing whole words so that the work done doesn't depend on the output.
+    ///
+    /// Every word of `dest`, including a partial last one, takes a full 64-bit draw and visits all eight of its byte positions,
+    /// so the only thing that affects timing is the length of `dest`, which is rarely secret.
+    /// This is a best-effort measure: the timing of the draws themselves depends on the RNG,
+    /// and the brain makes no guarantees about constant-time execution, so don't rely on it against a determined attacker.
+    fn constant_time_fill(&mut self, dest: &mut [u8]) {
+        for word in 0..dest.len().div_ceil(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            for (offset, &byte) in bytes.iter().enumerate() {
+                if let Some(slot) = dest.get_mut(word * 8 + offset) {
+                    *slot = byte;
+                }
+            }
+        }
+    }
+
+    /// Fill `dest` with random bytes, writing each drawn word in big-endian byte order.
+    ///
+    /// [`RngCore::fill_bytes`] writes words in little-endian order, which stays the default.
+    /// For pure randomness the byte order doesn't matter, but it does for reproducible test vectors
+    /// and protocol fields that are read back as big-endian integers.
+    /// Each 4-byte chunk of `dest` gets its own [`next_u32`](RngCore::next_u32),
+    /// so for [`SystemRng`](crate::SystemRng) and [`AdiRng`](crate::AdiRng), this produces the same bytes
+    /// as `fill_bytes` from the same state, reversed within every full 4-byte chunk.
+    fn fill_bytes_be(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let len = chunk.len();
+            let value = self.next_u32();
+            chunk.copy_from_slice(&value.to_be_bytes()[..len]);
+        }
+    }
+
+    /// Returns a uniformly random Q16.16 fixed-point number in `[0, 1)`.
+    ///
+    /// The upper 16 bits (the integer part) are always zero, and the lower 16 bits (the fraction) are taken
+    /// from the top bits of a draw. Dividing the result by `65536` gives the equivalent `f32`,
+    /// but code doing integer-only math can use it as-is.
+    fn next_fixed_q16(&mut self) -> u32 {
+        self.next_u32() >> 16
+    }
+
+    /// Returns a [`FastRng`] seeded from a single draw of this RNG.
+    ///
+    /// Use this when a burst of numbers is needed and fresh hardware entropy for each of them isn't,
+    /// for example when initializing many particles in one frame. The fast generator never reads the hardware again,
+    /// so every number it produces is derived from that one draw.
+    /// For anything that needs to stay unpredictable, prefer `into_reseeding_chacha` with the `crypto` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let mut fast = rng.fast();
+    ///     let offsets: [u64; 64] = core::array::from_fn(|_| fast.next_u64());
+    /// }
+    /// ```
+    fn fast(&mut self) -> FastRng {
+        FastRng::new(self.next_u64())
+    }
+
+    /// Wrap this RNG in a [`U32Rng`], which serves both halves of each 64-bit draw as separate `next_u32` results.
+    ///
+    /// For code that only draws `u32`s, this halves how often the hardware is sampled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new().downgrade_to_u32_core();
+    ///     let speeds: [u32; 8] = core::array::from_fn(|_| rng.next_u32() % 600);
+    /// }
+    /// ```
+    fn downgrade_to_u32_core(self) -> U32Rng<Self>
+    where
+        Self: Sized,
+    {
+        U32Rng::new(self)
+    }
+
+    /// Returns a random value that is never zero.
+    ///
+    /// Draws that come out as zero are redrawn, which only ever happens by chance with probability `2^-64`.
+    /// A source that keeps producing zero has collapsed entirely, so after 16 zero draws in a row
+    /// this gives up and returns `1`, or panics if the `panic-on-collapse` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let salt = rng.next_nonzero_u64();
+    ///     println!("Salt: {salt}");
+    /// }
+    /// ```
+    fn next_nonzero_u64(&mut self) -> NonZeroU64 {
+        for _ in 0..MAX_NONZERO_RETRIES {
+            if let Some(value) = NonZeroU64::new(self.next_u64()) {
+                return value;
+            }
+        }
+
+        #[cfg(feature = "panic-on-collapse")]
+        panic!(
+            "every entropy source has collapsed: drew zero {MAX_NONZERO_RETRIES} times in a row"
+        );
+        #[cfg(not(feature = "panic-on-collapse"))]
+        NonZeroU64::MIN
+    }
+
+    /// Returns two independent uniform floats in `[0, 1)` from a single draw.
+    ///
+    /// Each float is made from one half of the drawn word, so it has 32 bits of precision instead of 53,
+    /// which is plenty for sampling points and directions, and only samples the hardware once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let (x, y) = rng.next_f64_pair();
+    ///     println!("Particle at ({x}, {y})");
+    /// }
+    /// ```
+    fn next_f64_pair(&mut self) -> (f64, f64) {
+        const SCALE: f64 = 1.0 / (1u64 << 32) as f64;
+        let value = self.next_u64();
+        (
+            f64::from(value as u32) * SCALE,
+            f64::from((value >> 32) as u32) * SCALE,
+        )
+    }
+
+    /// Returns an endless iterator of booleans that are each `true` with probability `p`.
+    ///
+    /// Each flip compares 32 random bits against `p`, so every draw from this RNG is shared between two flips,
+    /// and `p` is only resolved to the nearest multiple of `2^-32`. `p` is checked once, when the iterator is created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not between `0.0` and `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let hits = rng.bool_iter(0.3).take(1_000).filter(|&hit| hit).count();
+    ///     println!("{hits} of 1000 trials hit");
+    /// }
+    /// ```
+    #[track_caller]
+    fn bool_iter(&mut self, p: f64) -> impl Iterator<Item = bool> + '_ {
+        assert!(
+            (0.0..=1.0).contains(&p),
+            "bool_iter requires a probability between 0.0 and 1.0, but was given {p}"
+        );
+        // The threshold is kept as a `u64` so that `p = 1.0`, a threshold of `2^32`, is always true.
+        let threshold = (p * (1u64 << 32) as f64) as u64;
+        let mut buffered = None;
+        core::iter::from_fn(move || {
+            let bits = match buffered.take() {
+                Some(bits) => bits,
+                None => {
+                    let value = self.next_u64();
+                    buffered = Some(value >> 32);
+                    value & u64::from(u32::MAX)
+                }
+            };
+            Some(bits < threshold)
+        })
+    }
+
+    /// Returns one of `options`, chosen uniformly at random.
+    ///
+    /// This is a convenience for small fixed sets, like a handful of allowed motor voltages,
+    /// that avoids borrowing a slice just to pick from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let voltage = rng.choose_from([-12.0, -6.0, 0.0, 6.0, 12.0]);
+    ///     println!("Testing at {voltage} V");
+    /// }
+    /// ```
+    #[track_caller]
+    fn choose_from<T: Copy, const N: usize>(&mut self, options: [T; N]) -> T {
+        assert!(
+            N > 0,
+            "choose_from requires at least one option, but was given an empty array"
+        );
+        options[self.random_range(0..N)]
+    }
+
+    /// Flip `n` coins at once, returning the results packed into the low `n` bits of a word.
+    ///
+    /// Bit `i` is the result of the `i`th flip, and every bit above the low `n` is zero.
+    /// This takes a single draw, so it is much cheaper than `n` calls to [`random_bool`](Rng::random_bool).
+    /// Each bit is only as fair as the RNG's output bits, so the flips are fair as long as
+    /// the RNG passes the monobit test in [`quick_statistical_test`](Self::quick_statistical_test).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let flips = rng.coin_flips(8);
+    ///     for robot in 0..8 {
+    ///         let go_left = flips >> robot & 1 == 1;
+    ///         println!("robot {robot} goes {}", if go_left { "left" } else { "right" });
+    ///     }
+    /// }
+    /// ```
+    #[track_caller]
+    fn coin_flips(&mut self, n: u32) -> u64 {
+        assert!(
+            n <= 64,
+            "coin_flips can flip at most 64 coins at once, but was asked to flip {n}"
+        );
+        match n {
+            0 => 0,
+            _ => self.next_u64() >> (64 - n),
+        }
+    }
+
+    /// Returns a value from `range`, including both of its ends, chosen uniformly at random.
+    ///
+    /// This is meant for ranges like `1..=6`, and handles ones that end at `u64::MAX` without the overflow
+    /// that converting to an exclusive range with `end + 1` would cause. The full `0..=u64::MAX` range is a raw draw.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let roll = rng.gen_range_inclusive(1..=6);
+    ///     println!("Rolled a {roll}");
+    /// }
+    /// ```
+    #[track_caller]
+    fn gen_range_inclusive(&mut self, range: RangeInclusive<u64>) -> u64 {
+        assert!(
+            !range.is_empty(),
+            "gen_range_inclusive requires a non-empty range, but was given {}..={}",
+            range.start(),
+            range.end()
+        );
+        let (start, end) = range.into_inner();
+        match (end - start).checked_add(1) {
+            Some(len) => start + self.random_range(0..len),
+            None => self.next_u64(),
+        }
+    }
+
+    /// Scramble `slice` in place with `swaps` random pairwise swaps.
+    ///
+    /// This is a cheaper alternative to a full shuffle for large buffers that only need to look mixed up,
+    /// like a particle buffer re-randomized every frame. Each swap takes a single draw,
+    /// split into two slightly biased indices, so the result is **not** a uniform permutation,
+    /// and a handful of swaps leaves most of the slice in place. Use a real shuffle when fairness matters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let mut particles = [0u16; 256];
+    ///     rng.scramble(&mut particles, 32);
+    /// }
+    /// ```
+    fn scramble<T>(&mut self, slice: &mut [T], swaps: usize) {
+        let len = slice.len();
+        if len < 2 {
+            return;
+        }
+        for _ in 0..swaps {
+            let value = self.next_u64();
+            if let Ok(len) = u32::try_from(len) {
+                // Scale each half of the draw into the slice, which is fast and close enough to uniform here.
+                let a = (u64::from(value as u32) * u64::from(len)) >> 32;
+                let b = ((value >> 32) * u64::from(len)) >> 32;
+                slice.swap(a as usize, b as usize);
+            } else {
+                slice.swap(value as usize % len, self.next_u64() as usize % len);
+            }
+        }
+    }
+
+    /// Fill `buf` with random ASCII letters and digits.
+    ///
+    /// Each character is chosen uniformly from the 62 alphanumeric characters.
+    fn fill_alphanumeric(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            *byte = ALPHANUMERIC[self.random_range(0..ALPHANUMERIC.len())];
+        }
+    }
+
+    /// Returns a string of `len` random ASCII letters and digits.
+    ///
+    /// This is useful for generating human-readable IDs for matches, sessions, and logs.
+    #[cfg(feature = "alloc")]
+    fn random_alphanumeric(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| char::from(ALPHANUMERIC[self.random_range(0..ALPHANUMERIC.len())]))
+            .collect()
+    }
+
+    /// Returns `n` independent samples from a normal distribution with the given `mean` and `std_dev`.
+    ///
+    /// Samples are generated with the Marsaglia polar method, which produces them in pairs,
+    /// so the second sample of every pair is used for the next element instead of being thrown away.
+    /// This is handy for generating a batch of process noise for a filter at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let noise = rng.gaussian_vec(3, 0.0, 0.05);
+    ///     println!("Process noise: {noise:?}");
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn gaussian_vec(&mut self, n: usize, mean: f64, std_dev: f64) -> Vec<f64> {
+        let mut samples = Vec::with_capacity(n);
+        while samples.len() < n {
+            let (u, v, s) = loop {
+                let (u, v) = (
+                    self.random::<f64>() * 2.0 - 1.0,
+                    self.random::<f64>() * 2.0 - 1.0,
+                );
+                let s = u * u + v * v;
+                if s > 0.0 && s < 1.0 {
+                    break (u, v, s);
+                }
+            };
+            let scale = Float::sqrt(-2.0 * Float::ln(s) / s);
+            samples.push(mean + std_dev * u * scale);
+            if samples.len() < n {
+                samples.push(mean + std_dev * v * scale);
+            }
+        }
+        samples
+    }
+
+    /// Reseed a [`nanorand`](https://crates.io/crates/nanorand) generator with bytes drawn from this RNG.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nanorand::{Rng, WyRand};
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut wyrand = WyRand::new_seed(0);
+    ///     SystemRng::new().seed_nanorand(&mut wyrand);
+    ///     println!("Random number: {}", wyrand.generate::<u64>());
+    /// }
+    /// ```
+    #[cfg(feature = "nanorand")]
+    fn seed_nanorand<const SEED_SIZE: usize, const OUTPUT: usize>(
+        &mut self,
+        rng: &mut impl nanorand::SeedableRng<SEED_SIZE, OUTPUT>,
+    ) {
+        let mut seed = [0; SEED_SIZE];
+        self.fill_bytes(&mut seed);
+        rng.reseed(seed);
+    }
+
+    /// Consume this RNG, returning an endless iterator of random [`u64`]s.
+    ///
+    /// This is convenient for one-shot generation when the RNG won't be used again.
+    /// To keep using the RNG, pass [`gen_fn`](Self::gen_fn) to [`core::iter::repeat_with`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     for value in SystemRng::new().into_u64_iter().take(8) {
+    ///         println!("{value}");
+    ///     }
+    /// }
+    /// ```
+    fn into_u64_iter(mut self) -> impl Iterator<Item = u64>
+    where
+        Self: Sized,
+    {
+        core::iter::repeat_with(move || self.next_u64())
+    }
+
+    /// Consume this RNG, returning an endless iterator of random bytes.
+    ///
+    /// Each drawn word is split into eight bytes, so no output is wasted.
+    fn into_bytes(self) -> impl Iterator<Item = u8>
+    where
+        Self: Sized,
+    {
+        self.into_u64_iter().flat_map(u64::to_le_bytes)
+    }
+
+    /// Fill `dest` with random bytes, yielding to the executor between chunks.
+    ///
+    /// Filling a large buffer takes many hardware reads, which can keep other tasks (like rendering)
+    /// from running for too long. This fills `dest` 64 bytes at a time and lets other tasks make progress in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let mut buf = [0; 1024];
+    ///     rng.fill_bytes_async(&mut buf).await;
+    /// }
+    /// ```
+    fn fill_bytes_async<'a>(&'a mut self, dest: &'a mut [u8]) -> impl Future<Output = ()> + 'a {
+        async move {
+            let mut chunks = dest.chunks_mut(ASYNC_FILL_CHUNK_LEN).peekable();
+            while let Some(chunk) = chunks.next() {
+                self.fill_bytes(chunk);
+                if chunks.peek().is_some() {
+                    YieldNow(false).await;
+                }
+            }
+        }
+    }
+
+    /// Returns a full [`heapless::Vec`] of `N` random bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let nonce = rng.fill_heapless::<12>();
+    ///     println!("{nonce:?}");
+    /// }
+    /// ```
+    #[cfg(feature = "heapless")]
+    fn fill_heapless<const N: usize>(&mut self) -> heapless::Vec<u8, N> {
+        let mut bytes = [0; N];
+        self.fill_bytes(&mut bytes);
+        heapless::Vec::from_array(bytes)
+    }
+
+    /// Returns a closure that draws a [`u64`] from this RNG each time it is called.
+    ///
+    /// This plugs directly into iterator adapters such as [`core::iter::repeat_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::iter;
+    ///
+    /// use veranda::{SystemRng, VerandaRngExt};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let sum: u64 = iter::repeat_with(rng.gen_fn())
+    ///         .take(4)
+    ///         .fold(0, u64::wrapping_add);
+    ///     println!("{sum}");
+    /// }
+    /// ```
+    fn gen_fn(&mut self) -> impl FnMut() -> u64 + '_ {
+        move || self.next_u64()
+    }
+}
+impl<R: RngCore + ?Sized> VerandaRngExt for R {}
+
+/// A future that returns control to the executor once before completing.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        cell::Cell,
+        cmp::Ordering,
+        future::Future,
+        hash::Hasher,
+        task::{Context, Waker},
+        time::Duration,
+    };
+
+    use rand::{rngs::mock::StepRng, RngCore};
+
+    use std::{
+        boxed::Box,
+        panic::AssertUnwindSafe,
+        string::String,
+        sync::{Arc, Mutex, PoisonError},
+    };
+
+    use super::{VerandaRngExt, ASYNC_FILL_CHUNK_LEN, MAX_NONZERO_RETRIES};
+    use crate::{backend::mock, AdiRng, MixInput, MixStrategy, SystemRng};
+
+    /// A source that draws zero until its budget of zeros runs out, then falls back to a real RNG.
+    struct ZeroBiased {
+        zeros: usize,
+        rng: SystemRng,
+    }
+
+    impl RngCore for ZeroBiased {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            if self.zeros == 0 {
+                return self.rng.next_u64();
+            }
+            self.zeros -= 1;
+            0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest {
+                *byte = self.next_u64() as u8;
+            }
+        }
+    }
+
+    /// A source with some bits forced on and others forced off.
+    struct Masked {
+        rng: SystemRng,
+        set: u64,
+        clear: u64,
+    }
+
+    impl Masked {
+        fn new(set: u64, clear: u64) -> Self {
+            Self {
+                rng: SystemRng::new(),
+                set,
+                clear,
+            }
+        }
+    }
+
+    impl RngCore for Masked {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            (self.rng.next_u64() | self.set) & !self.clear
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let len = chunk.len();
+                chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn benchmark_reports_the_mocked_timing() {
+        mock::install(mock::Mock {
+            time_step: 100,
+            ..mock::Mock::frozen()
+        });
+        // The step RNG never reads the clock, so every read is the benchmark's own, 100µs apart.
+        let report = StepRng::new(0, 1).benchmark(Duration::from_millis(1));
+        assert_eq!(report.draws, 10);
+        assert_eq!(report.elapsed, Duration::from_millis(1));
+        assert_eq!(report.draws_per_second(), 10_000.0);
+        assert_eq!(report.distinct_ratio(), 1.0);
+    }
+
+    #[test]
+    fn next_nonzero_u64_never_returns_zero() {
+        let mut rng = SystemRng::new();
+        for _ in 0..1000 {
+            assert_ne!(rng.next_nonzero_u64().get(), 0);
+        }
+    }
+
+    #[test]
+    fn next_nonzero_u64_redraws_zeros() {
+        let mut rng = ZeroBiased {
+            zeros: MAX_NONZERO_RETRIES - 1,
+            rng: SystemRng::new(),
+        };
+        assert_ne!(rng.next_nonzero_u64().get(), 0);
+        assert_eq!(rng.zeros, 0);
+    }
+
+    #[cfg(not(feature = "panic-on-collapse"))]
+    #[test]
+    fn next_nonzero_u64_gives_up_on_a_collapsed_source() {
+        let mut rng = ZeroBiased {
+            zeros: usize::MAX,
+            rng: SystemRng::new(),
+        };
+        assert_eq!(rng.next_nonzero_u64().get(), 1);
+        assert_eq!(rng.zeros, usize::MAX - MAX_NONZERO_RETRIES);
+    }
+
+    #[cfg(feature = "panic-on-collapse")]
+    #[test]
+    fn next_nonzero_u64_panics_on_a_collapsed_source() {
+        let mut rng = ZeroBiased {
+            zeros: usize::MAX,
+            rng: SystemRng::new(),
+        };
+        let (message, _) = panic_of(|| {
+            rng.next_nonzero_u64();
+        });
+        assert!(message.contains("collapsed"), "{message}");
+        assert_eq!(rng.zeros, usize::MAX - MAX_NONZERO_RETRIES);
+    }
+
+    #[test]
+    fn alphanumeric_fills_use_only_letters_and_digits() {
+        let mut rng = SystemRng::new();
+        let mut buf = [0; 64];
+        rng.fill_alphanumeric(&mut buf);
+        assert!(buf.iter().all(u8::is_ascii_alphanumeric));
+        assert!(buf.iter().any(|&byte| byte != buf[0]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn random_alphanumeric_has_the_requested_length() {
+        let mut rng = SystemRng::new();
+        for len in [0, 1, 12, 100] {
+            let id = rng.random_alphanumeric(len);
+            assert_eq!(id.len(), len);
+            assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
+        }
+    }
+
+    #[test]
+    fn fixed_point_draws_are_uniform_fractions() {
+        let mut rng = SystemRng::new();
+        let mut quarters = [0; 4];
+        for _ in 0..4000 {
+            let value = rng.next_fixed_q16();
+            assert_eq!(value >> 16, 0);
+            quarters[(value >> 14) as usize] += 1;
+        }
+        assert!(
+            quarters.iter().all(|&count| (800..1200).contains(&count)),
+            "{quarters:?}"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn gen_fn_works_with_repeat_with() {
+        let mut rng = SystemRng::new();
+        let values: alloc::vec::Vec<u64> = core::iter::repeat_with(rng.gen_fn()).take(4).collect();
+        assert_eq!(values.len(), 4);
+        assert!((1..4).all(|i| !values[..i].contains(&values[i])));
+    }
+
+    #[test]
+    fn a_stuck_bit_shows_in_the_frequency_report() {
+        let report = Masked::new(1 << 5, 1 << 40).bit_frequency_report(4000);
+        assert_eq!(report[5], 1.0);
+        assert_eq!(report[40], 0.0);
+        for (bit, frequency) in report.iter().enumerate() {
+            if bit != 5 && bit != 40 {
+                assert!((frequency - 0.5).abs() < 0.05, "bit {bit}: {frequency}");
+            }
+        }
+    }
+
+    #[test]
+    fn async_fills_yield_between_chunks() {
+        let mut rng = SystemRng::new();
+        let mut buf = [0; 200];
+        let mut yields = 0;
+        {
+            let mut future = core::pin::pin!(rng.fill_bytes_async(&mut buf));
+            let mut cx = Context::from_waker(Waker::noop());
+            while future.as_mut().poll(&mut cx).is_pending() {
+                yields += 1;
+            }
+        }
+        assert_eq!(yields, 200usize.div_ceil(ASYNC_FILL_CHUNK_LEN) - 1);
+        assert!(buf.iter().any(|&byte| byte != buf[0]));
+    }
+
+    #[test]
+    fn choose_from_picks_every_option_evenly() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let options = [-12, -6, 0, 6, 12];
+        let mut counts = [0; 5];
+        for _ in 0..5000 {
+            let voltage = rng.choose_from(options);
+            counts[options
+                .iter()
+                .position(|&option| option == voltage)
+                .unwrap()] += 1;
+        }
+        assert!(
+            counts.iter().all(|&count| (850..1150).contains(&count)),
+            "{counts:?}"
+        );
+    }
+
+    #[test]
+    fn big_endian_fills_reverse_each_word() {
+        mock::install(mock::Mock::frozen());
+        let mut little = [0; 16];
+        SystemRng::new().fill_bytes(&mut little);
+        let mut big = [0; 16];
+        SystemRng::new().fill_bytes_be(&mut big);
+        assert_ne!(little, big);
+        for (little, big) in little.chunks_exact(4).zip(big.chunks_exact(4)) {
+            assert!(little.iter().eq(big.iter().rev()));
+        }
+    }
+
+    #[test]
+    fn the_statistical_test_catches_a_biased_source() {
+        mock::install(mock::Mock::frozen());
+        let outcome = SystemRng::new().quick_statistical_test(1000);
+        assert_eq!(outcome.bits, 64_000);
+        assert!(outcome.passed(), "{outcome:?}");
+
+        // Forcing a quarter of the bits on leaves the stream far from balanced.
+        let outcome = Masked::new(0xffff, 0).quick_statistical_test(1000);
+        assert!(!outcome.monobit_passed(), "{outcome:?}");
+        assert!(!outcome.passed());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn consuming_iterators_collect_distinct_values() {
+        let values: alloc::vec::Vec<u64> = SystemRng::new().into_u64_iter().take(8).collect();
+        assert!((1..8).all(|i| !values[..i].contains(&values[i])));
+
+        let mut rng = StepRng::new(0x0807_0605_0403_0201, 0);
+        let bytes: alloc::vec::Vec<u8> = rng.clone().into_bytes().take(10).collect();
+        assert_eq!(bytes[..8], rng.next_u64().to_le_bytes());
+        assert_eq!(bytes[8..], [1, 2]);
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless_fills_are_full() {
+        let bytes = SystemRng::new().fill_heapless::<32>();
+        assert_eq!(bytes.len(), 32);
+        assert!(bytes.is_full());
+        assert!(bytes.iter().any(|&byte| byte != bytes[0]));
+    }
+
+    #[test]
+    fn chi_square_separates_uniform_and_skewed_bytes() {
+        mock::install(mock::Mock::frozen());
+        let uniform = SystemRng::new().chi_square_bytes(25_600);
+        assert!((200.0..310.5).contains(&uniform), "{uniform}");
+        // Clearing the top bit of every byte leaves half of the byte values unused.
+        let skewed = Masked::new(0, 0x8080_8080_8080_8080).chi_square_bytes(25_600);
+        assert!(skewed > 10.0 * uniform, "{skewed}");
+        assert_eq!(SystemRng::new().chi_square_bytes(0), 0.0);
+    }
+
+    #[test]
+    fn histograms_are_flat_unless_the_source_is_biased() {
+        mock::install(mock::Mock::frozen());
+        let uniform = SystemRng::new().byte_histogram(25_600);
+        assert_eq!(uniform.iter().sum::<u32>(), 25_600);
+        assert!(
+            uniform.iter().all(|&count| (50..160).contains(&count)),
+            "{uniform:?}"
+        );
+        // Clearing the low seven bits leaves only two byte values, each drawn about half of the time.
+        let biased = Masked::new(0, 0x7f).byte_histogram(25_600);
+        assert!(biased[0] > 10_000 && biased[0x80] > 10_000, "{biased:?}");
+        assert_eq!(biased[0] + biased[0x80], 25_600);
+    }
+
+    #[test]
+    fn inclusive_ranges_cover_both_ends() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let mut faces = [0; 6];
+        for _ in 0..6000 {
+            faces[rng.gen_range_inclusive(1..=6) as usize - 1] += 1;
+        }
+        assert!(
+            faces.iter().all(|&count| (850..1150).contains(&count)),
+            "{faces:?}"
+        );
+
+        assert_eq!(rng.gen_range_inclusive(4..=4), 4);
+        assert!(rng.gen_range_inclusive(u64::MAX - 1..=u64::MAX) >= u64::MAX - 1);
+        let mut step = StepRng::new(42, 0);
+        assert_eq!(step.gen_range_inclusive(0..=u64::MAX), 42);
+    }
+
+    #[test]
+    fn every_coin_is_fair() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let mut heads = [0; 40];
+        for _ in 0..4000 {
+            let flips = rng.coin_flips(40);
+            assert_eq!(flips >> 40, 0);
+            for (bit, count) in heads.iter_mut().enumerate() {
+                *count += (flips >> bit) & 1;
+            }
+        }
+        assert!(
+            heads.iter().all(|&count| (1800..2200).contains(&count)),
+            "{heads:?}"
+        );
+        assert_eq!(rng.coin_flips(0), 0);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn gaussian_batches_match_the_requested_parameters() {
+        mock::install(mock::Mock::frozen());
+        let samples = SystemRng::new().gaussian_vec(10_001, 3.0, 0.5);
+        assert_eq!(samples.len(), 10_001);
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+        assert!((mean - 3.0).abs() < 0.03, "{mean}");
+        assert!((variance.sqrt() - 0.5).abs() < 0.03, "{variance}");
+    }
+
+    #[test]
+    fn constant_time_fills_draw_one_word_per_eight_bytes() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        for len in [0, 1, 7, 8, 9, 31, 64, 257] {
+            let mut buffer = [0u8; 257];
+            let hashes = mock::calls().powerup_time;
+            rng.constant_time_fill(&mut buffer[..len]);
+            assert_eq!(mock::calls().powerup_time - hashes, len.div_ceil(8) as u64);
+            assert!(buffer[len..].iter().all(|&byte| byte == 0));
+            if len >= 8 {
+                assert!(buffer[..len].iter().any(|&byte| byte != buffer[0]), "{len}");
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_coins_land_true_at_the_requested_rate() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        for p in [0.0, 0.1, 0.25, 0.5, 0.9, 1.0] {
+            let trues = rng.bool_iter(p).take(10_000).filter(|&b| b).count();
+            let fraction = trues as f64 / 10_000.0;
+            assert!((fraction - p).abs() < 0.02, "{p}: {fraction}");
+        }
+        assert!(rng.bool_iter(0.0).take(1000).all(|b| !b));
+        assert!(rng.bool_iter(1.0).take(1000).all(|b| b));
+    }
+
+    #[test]
+    fn scrambling_permutes_with_one_draw_per_swap() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let mut particles: [u16; 256] = core::array::from_fn(|index| index as u16);
+        rng.scramble(&mut particles, 32);
+        // A full shuffle would take one draw for every element but the last.
+        assert_eq!(rng.draw_count(), 32);
+        assert!(particles
+            .iter()
+            .enumerate()
+            .any(|(index, &particle)| index as u16 != particle));
+        particles.sort_unstable();
+        assert!(particles
+            .iter()
+            .enumerate()
+            .all(|(index, &particle)| index as u16 == particle));
+    }
+
+    #[test]
+    fn a_working_rng_outranks_a_stuck_one() {
+        /// A strategy that ignores every input, so the RNG's output never changes.
+        struct Stuck;
+
+        impl MixStrategy for Stuck {
+            fn mix(_: &mut dyn Hasher, _: &[MixInput<'_>]) {}
+        }
+
+        let ports = mock::ports::<3>();
+        let mut healthy = AdiRng::new(&ports);
+        let mut stuck = AdiRng::new(&ports[..1]).with_mix_strategy(Stuck);
+        assert_eq!(healthy.compare_quality(&mut stuck, 500), Ordering::Greater);
+        assert_eq!(stuck.compare_quality(&mut healthy, 500), Ordering::Less);
+    }
+
+    #[test]
+    fn float_pairs_are_distinct_unit_values() {
+        let mut rng = SystemRng::new();
+        let first = rng.next_f64_pair();
+        for (x, y) in [first, rng.next_f64_pair()] {
+            assert!((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y));
+            assert_ne!(x, y);
+        }
+        assert_ne!(rng.next_f64_pair(), first);
+        assert_eq!(
+            StepRng::new(u64::MAX, 0).next_f64_pair().0,
+            1.0 - 1.0 / 4_294_967_296.0
+        );
+    }
+
+    /// Run `f`, which must panic, returning its panic message and the line the panic was reported at.
+    ///
+    /// The panic hook is shared by the whole process, so every test that replaces it holds `HOOK` while it does.
+    /// Panics on other threads in the meantime are passed on to the previous hook, so their output isn't lost.
+    fn panic_of(f: impl FnOnce()) -> (String, u32) {
+        static HOOK: Mutex<()> = Mutex::new(());
+        std::thread_local! {
+            static CAPTURING: Cell<bool> = const { Cell::new(false) };
+            static PANIC_LINE: Cell<Option<u32>> = const { Cell::new(None) };
+        }
+
+        let _guard = HOOK.lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = Arc::new(std::panic::take_hook());
+        std::panic::set_hook(Box::new({
+            let previous = previous.clone();
+            move |info| {
+                if CAPTURING.get() {
+                    PANIC_LINE.set(info.location().map(|location| location.line()));
+                } else {
+                    previous(info);
+                }
+            }
+        }));
+        CAPTURING.set(true);
+        let payload = std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        CAPTURING.set(false);
+        drop(std::panic::take_hook());
+        std::panic::set_hook(Arc::into_inner(previous).unwrap());
+        let line = PANIC_LINE.take().unwrap();
+        (*payload.downcast::<String>().unwrap(), line)
+    }
+
+    #[test]
+    fn selection_panics_name_the_bounds_and_the_caller() {
+        let mut rng = SystemRng::new();
+        #[allow(clippy::reversed_empty_ranges)]
+        let (message, line) = panic_of(|| {
+            rng.gen_range_inclusive(5..=2);
+        });
+        assert_eq!(line, line!() - 2);
+        assert!(message.contains("5..=2"), "{message}");
+
+        let (message, line) = panic_of(|| {
+            rng.coin_flips(65);
+        });
+        assert_eq!(line, line!() - 2);
+        assert!(message.contains("65"), "{message}");
+    }
+}
@@ -0,0 +1,77 @@
+use rand::RngCore;
+
+/// The increment SplitMix64 adds to its state on every draw, `2^64 / φ`.
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A fast, non-cryptographic SplitMix64 generator for bursts of numbers that don't need fresh hardware entropy.
+///
+/// Every draw is a handful of arithmetic operations on a single word of state, and never reads the hardware.
+/// Seed one from a hardware RNG with [`VerandaRngExt::fast`](crate::VerandaRngExt::fast) when a tight loop
+/// needs many numbers within a single tick. Its output is entirely determined by the seed,
+/// so it is never any more unpredictable than the one draw it was seeded from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct FastRng {
+    state: u64,
+}
+impl FastRng {
+    /// Create a new `FastRng` starting from `seed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::FastRng;
+    ///
+    /// let mut rng = FastRng::new(42);
+    /// let value = rng.next_u64();
+    /// ```
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl RngCore for FastRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::FastRng;
+    use crate::{backend::mock, SystemRng, VerandaRngExt};
+
+    #[test]
+    fn matches_the_reference_splitmix64() {
+        let mut rng = FastRng::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+    }
+
+    #[test]
+    fn draws_never_touch_the_backend() {
+        let mut fast = SystemRng::new().fast();
+        let seeded = mock::calls();
+        let values: [u64; 64] = core::array::from_fn(|_| fast.next_u64());
+        assert_eq!(mock::calls(), seeded);
+        assert!((1..64).all(|i| !values[..i].contains(&values[i])));
+    }
+}
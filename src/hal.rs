@@ -0,0 +1,51 @@
+use core::convert::Infallible;
+
+use embedded_hal::blocking::rng::Read;
+use rand::RngCore;
+
+use crate::{AdiRng, SystemRng};
+
+/// Every read samples the system metrics, just like [`RngCore::fill_bytes`].
+impl Read for SystemRng {
+    type Error = Infallible;
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.fill_bytes(buffer);
+        Ok(())
+    }
+}
+
+/// Every read samples the ADI ports, just like [`RngCore::fill_bytes`].
+impl Read for AdiRng<'_> {
+    type Error = Infallible;
+
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.fill_bytes(buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::blocking::rng::Read;
+
+    use crate::{backend::mock, AdiRng, SystemRng};
+
+    /// Fill a buffer through any embedded-hal RNG, like a driver generic over one would.
+    fn read_through_hal(rng: &mut impl Read) -> [u8; 16] {
+        let mut buffer = [0; 16];
+        assert!(rng.read(&mut buffer).is_ok());
+        buffer
+    }
+
+    #[test]
+    fn rngs_satisfy_the_hal_trait() {
+        let ports = mock::ports::<2>();
+        for buffer in [
+            read_through_hal(&mut SystemRng::new()),
+            read_through_hal(&mut AdiRng::new(&ports)),
+        ] {
+            assert!(buffer.iter().any(|&byte| byte != buffer[0]));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use rand::RngCore;
+
+/// An RNG adapter that splits every 64-bit draw from an inner RNG into two `u32`s.
+///
+/// Veranda's hardware RNGs produce a full 64-bit hash per draw, and their `next_u32` throws half of it away.
+/// A `U32Rng` keeps the unused half and returns it from the next call instead, so code that only needs
+/// `u32`s samples the hardware half as often. Both halves come from the same hash,
+/// so together they carry no more entropy than the single draw.
+///
+/// [`next_u64`](RngCore::next_u64) and [`fill_bytes`](RngCore::fill_bytes) go straight to the inner RNG,
+/// leaving any buffered half for the next `next_u32`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct U32Rng<R> {
+    inner: R,
+    spare: Option<u32>,
+}
+impl<R: RngCore> U32Rng<R> {
+    /// Create a new `U32Rng` that splits the draws of `inner`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{SystemRng, U32Rng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = U32Rng::new(SystemRng::new());
+    ///     // Only the first of these samples the hardware.
+    ///     let (a, b) = (rng.next_u32(), rng.next_u32());
+    /// }
+    /// ```
+    pub const fn new(inner: R) -> Self {
+        Self { inner, spare: None }
+    }
+
+    /// Returns the inner RNG, discarding any buffered half.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: RngCore> RngCore for U32Rng<R> {
+    fn next_u32(&mut self) -> u32 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+        let value = self.inner.next_u64();
+        self.spare = Some((value >> 32) as u32);
+        value as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use crate::{backend::mock, SystemRng, VerandaRngExt};
+
+    #[test]
+    fn two_next_u32_calls_share_one_hash() {
+        let mut rng = SystemRng::new().downgrade_to_u32_core();
+        let before = mock::calls().powerup_time;
+        let (a, b) = (rng.next_u32(), rng.next_u32());
+        assert_eq!(mock::calls().powerup_time - before, 1);
+        assert_ne!(a, b);
+        rng.next_u32();
+        assert_eq!(mock::calls().powerup_time - before, 2);
+    }
+}
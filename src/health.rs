@@ -0,0 +1,316 @@
+//! Entropy estimates and health reporting for RNG configurations.
+
+use core::{fmt, hash::Hasher};
+
+use crate::{backend, hasher, source::EntropySource, state::State};
+#[cfg(feature = "panic-report")]
+use vexide_core::{println, sync::Mutex};
+
+/// A rough estimate of the entropy, in bits, contributed per draw by the system metrics.
+///
+/// The battery readings and uptimes change slowly, so only their lowest bits are unpredictable.
+/// Without the `battery` feature, only the uptimes are used and the estimate is halved.
+pub const SYSTEM_ENTROPY_BITS: f32 = if cfg!(feature = "battery") { 8.0 } else { 4.0 };
+
+/// A rough estimate of the entropy, in bits, contributed per draw by each empty ADI port.
+///
+/// Analog readings on a floating port are 12 bits, but only the lower few bits are noise.
+pub const ADI_PORT_ENTROPY_BITS: f32 = 4.0;
+
+/// A rough estimate of the entropy, in bits, contributed per draw by a connected but noisy analog device.
+///
+/// Only the lowest bit or so of a connected device's reading jitters.
+pub const NOISY_DEVICE_ENTROPY_BITS: f32 = 1.0;
+
+/// A problem with an RNG configuration that makes its output weaker than it could be.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum EntropyWarning {
+    /// An `AdiRng` was created without any ADI ports.
+    /// It will produce output no better than a `SystemRng`.
+    NoPorts,
+    /// An `AdiRng` was created with a single ADI port.
+    /// One port contributes much less entropy than several; consider passing more empty ports.
+    SinglePort,
+    /// A noisy device configured with `AdiRng::with_noisy_device` has stopped changing.
+    /// It is most likely outputting a fixed voltage and is not contributing any entropy.
+    ConstantDevice,
+    /// The brain's timers have stopped advancing between draws, so every hardware input has most likely collapsed.
+    ///
+    /// Output still changes from draw to draw, because an internal counter is mixed into every draw,
+    /// but that sequence is deterministic and is not random at all.
+    StuckTimers,
+    /// The last draw repeated the previous output even after being resampled,
+    /// which can only be reported when resampling is enabled with `with_resample_on_equal`.
+    ///
+    /// Consecutive equal outputs are practically impossible for a working RNG, so this most likely means a stuck source.
+    RepeatedOutput,
+}
+
+/// A snapshot of the health of an RNG.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyStatus {
+    /// The estimated entropy, in bits, contributed per draw.
+    pub estimated_bits: f32,
+    /// The most severe problem detected with the RNG, if any.
+    pub warning: Option<EntropyWarning>,
+    /// The number of calls that produced output, as returned by `draw_count`.
+    pub draw_count: u64,
+    /// The number of bytes those calls returned, as returned by `bytes_generated`.
+    pub bytes_generated: u64,
+}
+
+impl EntropyStatus {
+    /// Returns `true` if no problems were detected with the RNG.
+    pub const fn is_healthy(&self) -> bool {
+        self.warning.is_none()
+    }
+}
+
+/// The health of an RNG as of its latest draw, recorded for panic reports.
+#[cfg(feature = "panic-report")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordedHealth {
+    /// The RNG's status right after the draw.
+    pub status: EntropyStatus,
+    /// The number of draws the RNG had made since it was created or reset.
+    pub draws: u64,
+}
+
+/// The health recorded by the most recent draw of any RNG with health recording enabled.
+#[cfg(feature = "panic-report")]
+static LAST_HEALTH: Mutex<Option<RecordedHealth>> = Mutex::new(None);
+
+/// Store `health` as the most recently recorded health, replacing any previous record.
+///
+/// If the record is being read at the same moment, this draw's record is skipped rather than waiting.
+#[cfg(feature = "panic-report")]
+pub(crate) fn record_health(health: RecordedHealth) {
+    if let Some(mut last) = LAST_HEALTH.try_lock() {
+        *last = Some(health);
+    }
+}
+
+/// Returns the health recorded by the most recent draw of an RNG with health recording enabled, if any.
+///
+/// Recording is enabled with `with_health_recording` on [`SystemRng`](crate::SystemRng) or [`AdiRng`](crate::AdiRng).
+/// If several RNGs record their health, this is whichever drew last.
+#[cfg(feature = "panic-report")]
+pub fn last_recorded_health() -> Option<RecordedHealth> {
+    LAST_HEALTH.try_lock().and_then(|last| *last)
+}
+
+/// Print the most recently recorded RNG health to the console.
+///
+/// This is meant to be called from a panic hook, so that crashes caused by bad randomness can be diagnosed.
+/// It never blocks, so it is safe to call even if a panic happened while a draw was recording its health.
+///
+/// # Examples
+///
+/// ```
+/// use veranda::{health, SystemRng};
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(_: Peripherals) {
+///     vexide::panic::set_hook(|info| {
+///         health::print_recorded_health();
+///         vexide::panic::default_panic_hook(info);
+///     });
+///     let rng = SystemRng::new().with_health_recording();
+/// }
+/// ```
+#[cfg(feature = "panic-report")]
+pub fn print_recorded_health() {
+    match last_recorded_health() {
+        Some(RecordedHealth { status, draws }) => println!(
+            "veranda: {} draws, {:.1} estimated bits per draw, warning: {:?}",
+            draws, status.estimated_bits, status.warning
+        ),
+        None => println!("veranda: no RNG health recorded"),
+    }
+}
+
+/// A suggestion to switch from a [`SystemRng`](crate::SystemRng) to a stronger configuration,
+/// returned by [`SystemRng::advise`](crate::SystemRng::advise).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Advice {
+    /// The onboard ADI ports that look free, as a bit mask where bit `0` is port A and bit `7` is port H.
+    pub free_ports: u8,
+}
+
+impl Advice {
+    /// Returns the number of free ports that were found.
+    pub const fn free_port_count(&self) -> u32 {
+        self.free_ports.count_ones()
+    }
+
+    /// Returns the estimated entropy, in bits per draw, that passing every free port to an
+    /// [`AdiRng`](crate::AdiRng) or [`CombinedRng`](crate::CombinedRng) would add.
+    pub fn additional_bits(&self) -> f32 {
+        self.free_port_count() as f32 * ADI_PORT_ENTROPY_BITS
+    }
+}
+
+/// The minimum length of a buffer that is checked for collapsed output.
+///
+/// Shorter buffers are all one value by chance too often for the check to be meaningful.
+pub const MIN_CHECKED_FILL_LEN: usize = 8;
+
+/// An error returned when a buffer could not be safely filled with random bytes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FillError {
+    /// Every byte in the buffer had the same value.
+    /// This almost certainly means that every entropy source is stuck.
+    ConstantOutput,
+    /// The entropy budget of a crypto generator didn't cover the requested bytes.
+    /// More entropy must be gathered before it can emit them.
+    EntropyExhausted,
+}
+
+impl fmt::Display for FillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConstantOutput => {
+                write!(f, "the RNG filled a buffer with a single repeated byte")
+            }
+            Self::EntropyExhausted => {
+                write!(
+                    f,
+                    "the RNG has not gathered enough entropy to fill the buffer"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for FillError {}
+
+/// An error returned when an RNG configuration can't meet a required level of entropy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum InitError {
+    /// The configuration's estimated entropy per draw is below the required minimum.
+    InsufficientEntropy {
+        /// The configuration's estimated entropy, in bits per draw.
+        estimated_bits: f32,
+        /// The minimum entropy that was required, in bits per draw.
+        required_bits: f32,
+    },
+    /// The startup self-test read exactly the same system metrics every time,
+    /// so the hardware inputs have most likely collapsed.
+    SelfTestFailed,
+    /// One of the sources given for seeding wrote the same thing on every sample.
+    StuckSource {
+        /// The position of the stuck source in the list of sources.
+        index: usize,
+    },
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientEntropy {
+                estimated_bits,
+                required_bits,
+            } => write!(
+                f,
+                "the RNG provides an estimated {estimated_bits} bits of entropy per draw, but {required_bits} are required"
+            ),
+            Self::SelfTestFailed => write!(f, "the RNG's inputs never changed during its startup self-test"),
+            Self::StuckSource { index } => write!(f, "entropy source {index} is stuck"),
+        }
+    }
+}
+
+impl core::error::Error for InitError {}
+
+/// The number of times [`source_is_stuck`] samples a source.
+const SOURCE_TEST_SAMPLES: usize = 4;
+
+/// Sample `source` a few times, returning `true` if it wrote exactly the same thing every time.
+///
+/// A source that writes nothing at all is also reported as stuck.
+pub(crate) fn source_is_stuck(source: &mut dyn EntropySource) -> bool {
+    let mut first = None;
+    for _ in 0..SOURCE_TEST_SAMPLES {
+        let mut fingerprint = hasher();
+        source.sample(&mut fingerprint);
+        let fingerprint = fingerprint.finish();
+        if *first.get_or_insert(fingerprint) != fingerprint {
+            return false;
+        }
+    }
+    true
+}
+
+/// How many times the high-resolution timer is read while waiting for it to tick, before giving up.
+const TICK_WAIT_READS: u32 = 1000;
+
+/// Read the system metrics of `state` a few times, returning `true` if they were exactly the same every time.
+///
+/// The draw counter is mixed into every output, so a working RNG and one whose hardware has collapsed
+/// produce equally distinct output, and only the raw readings can tell them apart. Before each reading,
+/// this waits briefly for the high-resolution timer to tick, so working timers always differ between readings.
+/// Each reading advances the counter like a draw would.
+pub(crate) fn inputs_stuck(state: &mut State) -> bool {
+    let mut first = None;
+    for _ in 0..SOURCE_TEST_SAMPLES {
+        let start = backend::high_res_time();
+        for _ in 0..TICK_WAIT_READS {
+            if backend::high_res_time() != start {
+                break;
+            }
+        }
+        let sample = state.read_sample();
+        state.write_sample(&mut hasher(), &sample);
+        if *first.get_or_insert(sample) != sample {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns `true` if `bytes` is long enough to check and consists of a single repeated byte.
+pub(crate) fn is_collapsed(bytes: &[u8]) -> bool {
+    bytes.len() >= MIN_CHECKED_FILL_LEN && bytes.iter().all(|&byte| byte == bytes[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_collapsed, MIN_CHECKED_FILL_LEN};
+    use crate::SystemRng;
+
+    #[test]
+    fn an_all_zero_fill_is_collapsed() {
+        assert!(is_collapsed(&[0; 32]));
+        assert!(is_collapsed(&[0xff; MIN_CHECKED_FILL_LEN]));
+        assert!(!is_collapsed(&[0; MIN_CHECKED_FILL_LEN - 1]));
+        let mut varied = [0; 32];
+        varied[31] = 1;
+        assert!(!is_collapsed(&varied));
+    }
+
+    #[test]
+    fn a_healthy_fill_succeeds() {
+        let mut buf = [0; 32];
+        assert_eq!(SystemRng::new().try_fill_bytes(&mut buf), Ok(()));
+        assert!(!is_collapsed(&buf));
+    }
+
+    // This is the only test that records health, so no other test can race it for the global record.
+    #[cfg(feature = "panic-report")]
+    #[test]
+    fn recorded_health_follows_the_latest_draw() {
+        use rand::RngCore;
+
+        let mut rng = SystemRng::new().with_health_recording();
+        for draws in 1..=5 {
+            rng.next_u64();
+            let recorded = super::last_recorded_health().unwrap();
+            assert_eq!(recorded.draws, draws);
+            assert_eq!(recorded.status, rng.status());
+        }
+    }
+}
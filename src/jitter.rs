@@ -0,0 +1,181 @@
+use core::hash::Hasher;
+
+use rand::RngCore;
+use vexide_core::float::Float;
+
+use crate::{backend, hasher};
+
+/// The number of hash iterations in the workload timed by each round.
+const WORKLOAD_ITERATIONS: u32 = 64;
+
+/// The number of measurements taken by [`JitterRng::calibrate`].
+const CALIBRATION_MEASUREMENTS: usize = 256;
+
+/// The entropy, in bits, that each output is calibrated to contain.
+const TARGET_BITS: f32 = 64.0;
+
+/// An RNG that extracts entropy from jitter in the time the CPU takes to run a small workload.
+///
+/// Each output times a workload several times with the high-resolution timer and hashes the measurements together.
+/// Interrupts, caches, and the rest of the system make those timings vary slightly from run to run,
+/// so this works without any sensors or ports. It is much slower than the other RNGs.
+///
+/// The default number of rounds per output is conservative. Call [`calibrate`](Self::calibrate) to measure
+/// the jitter on the current brain and workload and adjust it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct JitterRng {
+    rounds: u32,
+    counter: u64,
+}
+impl JitterRng {
+    /// The number of rounds per output before calibration.
+    pub const DEFAULT_ROUNDS: u32 = 64;
+
+    /// The most rounds calibration will choose per output.
+    pub const MAX_ROUNDS: u32 = 1024;
+
+    /// Create a new `JitterRng` with [`DEFAULT_ROUNDS`](Self::DEFAULT_ROUNDS) rounds per output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::JitterRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = JitterRng::new();
+    ///     let bits = rng.calibrate();
+    ///     println!("{bits} bits per round, {} rounds per output", rng.rounds());
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            rounds: Self::DEFAULT_ROUNDS,
+            counter: 0,
+        }
+    }
+
+    /// Returns the number of timing rounds hashed into each output.
+    pub const fn rounds(&self) -> u32 {
+        self.rounds
+    }
+
+    /// Measure the timing jitter on this brain and adjust the number of rounds per output to match.
+    ///
+    /// This times the workload many times, estimates the entropy of each measurement from the variance of the timings,
+    /// and chooses enough rounds per output to gather about 64 bits, up to [`MAX_ROUNDS`](Self::MAX_ROUNDS).
+    /// The estimated bits per round are returned.
+    ///
+    /// The brain's timer only has microsecond resolution, so most of the jitter is too small to see,
+    /// and values well below one bit per round are typical. A result of `0.0` means that no jitter was
+    /// observed at all, and every output uses the maximum number of rounds.
+    pub fn calibrate(&mut self) -> f32 {
+        let mut sum = 0.0;
+        let mut sum_of_squares = 0.0;
+        for _ in 0..CALIBRATION_MEASUREMENTS {
+            let time = self.measure() as f64;
+            sum += time;
+            sum_of_squares += time * time;
+        }
+        let n = CALIBRATION_MEASUREMENTS as f64;
+        let mean = sum / n;
+        let variance = sum_of_squares / n - mean * mean;
+
+        // The differential entropy of a normal distribution with this variance, sampled at 1 µs.
+        let bits = if variance > 0.0 {
+            Float::log2(2.0 * core::f64::consts::PI * core::f64::consts::E * variance) / 2.0
+        } else {
+            0.0
+        };
+        let bits = (bits as f32).max(0.0);
+
+        self.rounds = if bits > 0.0 {
+            Float::ceil(TARGET_BITS / bits).min(Self::MAX_ROUNDS as f32) as u32
+        } else {
+            Self::MAX_ROUNDS
+        };
+        bits
+    }
+
+    /// Time a single run of the workload, in microseconds.
+    fn measure(&self) -> u64 {
+        measure_workload()
+    }
+}
+
+/// Time a single run of the jitter workload, in microseconds.
+pub(crate) fn measure_workload() -> u64 {
+    let start = backend::high_res_time();
+    let mut hasher = hasher();
+    for iteration in 0..WORKLOAD_ITERATIONS {
+        hasher.write_u32(iteration);
+    }
+    core::hint::black_box(hasher.finish());
+    backend::high_res_time().wrapping_sub(start)
+}
+
+impl Default for JitterRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for JitterRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut hasher = hasher();
+        for _ in 0..self.rounds {
+            hasher.write_u64(self.measure());
+        }
+        hasher.write_u64(backend::high_res_time());
+        hasher.write_u64(self.counter);
+        self.counter = self.counter.wrapping_add(1);
+        hasher.finish()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+
+    use super::JitterRng;
+    use crate::backend::mock;
+
+    #[test]
+    fn calibration_measures_the_timing_jitter() {
+        mock::install(mock::Mock {
+            time_step: 5,
+            ..mock::Mock::frozen()
+        });
+        let mut rng = JitterRng::new();
+        assert_eq!(rng.calibrate(), 0.0);
+        assert_eq!(rng.rounds(), JitterRng::MAX_ROUNDS);
+
+        let mut step = 0;
+        mock::install(mock::Mock {
+            time_steps: Some(Box::new(move || {
+                step = (step + 5) % 11;
+                step
+            })),
+            ..mock::Mock::frozen()
+        });
+        let bits = rng.calibrate();
+        assert!(bits > 0.0, "{bits}");
+        assert_eq!(rng.rounds(), (64.0 / bits).ceil() as u32);
+        assert!(rng.rounds() < JitterRng::MAX_ROUNDS);
+    }
+}
@@ -3,22 +3,232 @@
 //! A [`rand`](https://crates.io/crates/rand) RNG source for [vexide](https://vexide.dev) programs.
 //!
 //! Veranda uses system metrics like brain uptime, program uptime, battery current and voltage, and, optionally, empty ADI ports.
+//!
+//! If you have a free ADI port, start with [`CombinedRng::standard`], which combines every source with safe defaults.
+//! Otherwise, use [`SystemRng::max_entropy`].
+//! To check that a build hashes the way veranda was released with, call [`verify_self`] at startup.
+//!
+//! # Features
+//!
+//! - `alloc`: Enable helpers that return heap-allocated collections, like [`VerandaRngExt::random_alphanumeric`],
+//!   `boxed` on [`SystemRng`] and `AdiRng<'static>` for storing them as `Box<dyn RngCore + Send>`,
+//!   and [`reconfigure_global`] for installing any RNG as the [`global`] one.
+//! - `battery` (default): Mix battery voltage and current readings into every draw.
+//!   Without it, only the brain and program uptimes are used, which roughly halves the entropy of a `SystemRng`.
+//! - `crypto`: Enable [`CryptoVeranda`], a ChaCha20 generator keyed from hardware entropy with conservative entropy accounting,
+//!   and `into_reseeding_chacha` on the hardware RNGs, which is the recommended way to get fast output for most programs.
+//!   This also enables [`Secret`] and the `finalize_256_secret` methods, which keep seeds out of logs,
+//!   and `generate_key`, which refuses to produce key material from a source that fails its self-test.
+//! - `deterministic`: Generate every system reading from a global seed set with [`set_global_test_seed`]
+//!   instead of reading the hardware, so that tests using veranda are reproducible.
+//!   This implies `host` and can't be built for the brain, so it can never ship in a real program.
+//! - `embedded-hal`: Implement [`embedded-hal`](https://crates.io/crates/embedded-hal)'s blocking `rng::Read` trait
+//!   for [`SystemRng`] and [`AdiRng`], so drivers written against it can draw from veranda on the brain.
+//! - `heapless`: Add [`VerandaRngExt::fill_heapless`] for producing random bytes in a [`heapless`](https://crates.io/crates/heapless) vector.
+//! - `host`: Replace every hardware reading with a stub built on `std`, so programs using veranda can run on a computer.
+//!   The timers are backed by the system clock, and the battery and ADI readings are always zero.
+//!   This is only intended for testing, and must not be enabled when building for the brain.
+//! - `nanorand`: Implement [`nanorand`](https://crates.io/crates/nanorand)'s `Rng` trait for [`SystemRng`],
+//!   and add [`VerandaRngExt::seed_nanorand`] for seeding any of its generators from hardware entropy.
+//! - `panic-report`: Add `with_health_recording` to the hardware RNGs, which records their health after every draw,
+//!   and [`health::print_recorded_health`] for printing the latest record from a panic hook.
+//! - `panic-on-collapse`: Panic in [`RngCore::fill_bytes`] if the filled buffer is obviously not random,
+//!   and in [`VerandaRngExt::next_nonzero_u64`] if the RNG keeps drawing zero.
+//!   By default, only the fallible `try_fill_bytes` methods check their output.
 
 #![no_std]
 
-use core::hash::{BuildHasher, BuildHasherDefault, Hasher};
+#[cfg(all(feature = "deterministic", target_vendor = "vex"))]
+compile_error!(
+    "the `deterministic` feature makes every RNG predictable and must not be enabled on the brain"
+);
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(test, feature = "host"))]
+extern crate std;
+
+mod accumulator;
+mod adi;
+mod any;
+mod backend;
+mod bits;
+mod buffer;
+mod combined;
+mod compare;
+mod composite;
+#[cfg(feature = "crypto")]
+mod crypto;
+mod current;
+mod deterministic;
+pub mod diagnostics;
+mod edge;
+mod ext;
+mod fast;
+#[cfg(feature = "embedded-hal")]
+mod hal;
+mod halves;
+pub mod health;
+mod jitter;
+mod link;
+#[macro_use]
+mod methods;
+mod mix;
+#[cfg(feature = "nanorand")]
+mod nano;
+mod noisy;
+mod pool;
+pub mod prelude;
+mod profile;
+mod rate;
+mod replay;
+#[cfg(feature = "crypto")]
+mod secret;
+mod shared;
+mod snapshot;
+pub mod source;
+mod state;
+mod trickle;
+mod whiten;
+mod xor;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::{
+    fmt,
+    hash::{BuildHasher, BuildHasherDefault, Hasher},
+    sync::atomic::AtomicU64,
+    time::Duration,
+};
 
 use ahash::AHasher;
-use rand::RngCore;
-use vex_sdk::{vexDeviceAdiValueGet, vexDeviceGetByIndex, vexSystemPowerupTimeGet};
-use vexide_core::time::Instant;
-use vexide_devices::{adi::AdiPort, battery};
+use backend::Instant;
+use buffer::ByteBuffer;
+use health::{
+    Advice, EntropyStatus, EntropyWarning, FillError, InitError, ADI_PORT_ENTROPY_BITS,
+    NOISY_DEVICE_ENTROPY_BITS, SYSTEM_ENTROPY_BITS,
+};
+use mix::MixFn;
+use noisy::NoisyDevice;
+#[cfg(feature = "crypto")]
+use rand::rngs::ReseedingRng;
+use rand::{distr::Distribution, RngCore};
+#[cfg(feature = "crypto")]
+use rand_chacha::ChaCha20Core;
+use source::{EntropySource, SourceFlags};
+use state::{StallAlarm, State, TickSource, MAX_RESAMPLES};
+use trickle::Trickle;
+use vexide_core::io;
+use vexide_devices::{adi::AdiPort, peripherals::Peripherals};
+
+pub use accumulator::EntropyAccumulator;
+pub use adi::AdiRngError;
+pub use any::AnyRng;
+pub use bits::RandomBits;
+pub use combined::CombinedRng;
+pub use compare::timing_safe_equal;
+pub use composite::CompositeRng;
+#[cfg(feature = "crypto")]
+pub use crypto::CryptoVeranda;
+pub use current::CurrentJitterSource;
+#[cfg(feature = "deterministic")]
+pub use deterministic::set_global_test_seed;
+pub use deterministic::verify_self;
+pub use edge::AdiEdgeSource;
+pub use ext::VerandaRngExt;
+pub use fast::FastRng;
+pub use halves::U32Rng;
+pub use jitter::JitterRng;
+pub use link::LinkSource;
+pub use mix::{
+    combine_seeds, derive_subseed, mix64, MixInput, MixStrategy, SequentialHash, XorOfHashes,
+};
+pub use pool::EntropyPool;
+pub use profile::EnvironmentProfile;
+pub use rate::RateLimited;
+pub use replay::{RawSample, Recording, ReplayRng};
+#[cfg(feature = "crypto")]
+pub use secret::Secret;
+#[cfg(feature = "alloc")]
+pub use shared::reconfigure_global;
+pub use shared::{global, GlobalRng, SharedRng};
+pub use snapshot::RngSnapshot;
+pub use whiten::Whitened;
+pub use xor::XorRng;
+
+// Every RNG must be usable from async tasks.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SystemRng>();
+    assert_send_sync::<AdiRng<'static>>();
+    assert_send_sync::<CompositeRng<AdiEdgeSource<'static>>>();
+    assert_send_sync::<SharedRng>();
+    assert_send_sync::<AnyRng<'static>>();
+    assert_send_sync::<RateLimited<SystemRng>>();
+};
+
+/// The version of the hashing that turns entropy into output and seeds.
+///
+/// This changes whenever an update to veranda changes the output for identical inputs,
+/// so that recorded test vectors and derived seeds from another version can be detected and rejected.
+/// It is mixed into every 256-bit seed, including those from `finalize_256`, [`derive_subseed`], and [`combine_seeds`].
+pub const HASH_VERSION: u32 = 1;
+
+/// The number of times [`SystemRng::advise`] reads each port.
+const ADVICE_SAMPLES: usize = 4;
+
+fn hasher() -> AHasher {
+    BuildHasherDefault::<AHasher>::default().build_hasher()
+}
+
+/// Produce a 256-bit seed from an accumulated hasher by finishing it with the hash version and an incrementing counter.
+fn squeeze_256(accumulated: &AHasher) -> [u8; 32] {
+    squeeze_256_as(accumulated, HASH_VERSION)
+}
+
+/// Like [`squeeze_256`], but finishing with `version` in place of [`HASH_VERSION`].
+fn squeeze_256_as(accumulated: &AHasher, version: u32) -> [u8; 32] {
+    let mut seed = [0; 32];
+    for (counter, chunk) in seed.chunks_exact_mut(8).enumerate() {
+        let mut hasher = accumulated.clone();
+        hasher.write_u32(version);
+        hasher.write_u64(counter as u64);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    seed
+}
+
+/// Produce `N` distinct words from an accumulated hasher by finishing it with an incrementing counter.
+///
+/// A word that repeats an earlier one is skipped, so the words are distinct even in the astronomically unlikely case of a collision.
+fn squeeze_distinct<const N: usize>(accumulated: &AHasher) -> [u64; N] {
+    let mut words = [0; N];
+    let mut counter = 0u64;
+    for index in 0..N {
+        words[index] = loop {
+            let mut hasher = accumulated.clone();
+            hasher.write_u32(HASH_VERSION);
+            hasher.write_u64(counter);
+            counter += 1;
+            let word = hasher.finish();
+            if !words[..index].contains(&word) {
+                break word;
+            }
+        };
+    }
+    words
+}
 
 /// A [`rand`](https://crates.io/crates/rand) RNG source that only uses system metrics for entropy.
 /// This RNG source has a lower entropy than `AdiRng`, but does not require empty ADI ports.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+///
+/// `SystemRng` is `Send` and `Sync`, so it can be freely moved into spawned tasks.
+/// It is deliberately not `Copy`: a copy would hand out the same buffered bytes as the original.
+/// Use [`fork`](Self::fork) for an independent stream.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SystemRng {
-    time_of_creation: Instant,
+    state: State,
+    buffer: ByteBuffer,
 }
 impl SystemRng {
     /// Create a new `SystemRng`.
@@ -44,17 +254,481 @@ impl SystemRng {
     /// ```
     pub fn new() -> SystemRng {
         SystemRng {
-            time_of_creation: Instant::now(),
+            state: State::new(),
+            buffer: ByteBuffer::new(),
+        }
+    }
+
+    /// Create a new `SystemRng` with extra seed material of any length.
+    ///
+    /// The bytes are hashed into seed material that is mixed into every draw, so they augment the
+    /// system metrics rather than replacing them. This is useful for mixing in a secret like a team passphrase or a match token.
+    pub fn with_seed_bytes(bytes: &[u8]) -> SystemRng {
+        let mut rng = Self::new();
+        rng.state.mix_seed(|hasher| {
+            hasher.write_usize(bytes.len());
+            hasher.write(bytes);
+        });
+        rng
+    }
+
+    /// Create a new `SystemRng` with extra seed material read from an iterator of bytes.
+    ///
+    /// Every byte yielded by `iter` is hashed into the seed material, just like [`with_seed_bytes`](Self::with_seed_bytes),
+    /// but without needing the bytes to be collected into a slice first. This works well for streaming seed material
+    /// from a file or a sensor log.
+    pub fn from_byte_iter(iter: impl IntoIterator<Item = u8>) -> SystemRng {
+        let mut rng = Self::new();
+        rng.state.mix_seed(|hasher| {
+            for byte in iter {
+                hasher.write_u8(byte);
+            }
+        });
+        rng
+    }
+
+    /// Create a new `SystemRng` without reading the clock, so it can be used to initialize a `static`.
+    ///
+    /// The creation instant that [`SystemRng::new`] records immediately is instead anchored lazily,
+    /// on the first draw or call to [`ensure_init`](Self::ensure_init). Otherwise, the RNG behaves exactly the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{SharedRng, SystemRng};
+    ///
+    /// static RNG: SharedRng = SharedRng::new(SystemRng::uninit());
+    /// ```
+    pub const fn uninit() -> SystemRng {
+        SystemRng {
+            state: State::uninit(),
+            buffer: ByteBuffer::new(),
+        }
+    }
+
+    /// Anchor the creation instant of an RNG made with [`SystemRng::uninit`], if it hasn't been already.
+    ///
+    /// This happens automatically on the first draw, so calling it is only needed to anchor the instant earlier.
+    pub fn ensure_init(&mut self) {
+        self.state.ensure_init();
+    }
+
+    /// Create a new `SystemRng` with every available system input enabled.
+    ///
+    /// This is a good default when you just want the most unpredictable `SystemRng` possible:
+    /// every [source](SourceFlags::ALL) is enabled, the battery readings are mixed with
+    /// [noise amplification](Self::with_battery_noise_amplification), and the
+    /// [device identity](Self::with_device_identity) and [stack address](Self::with_address_entropy) are mixed in once,
+    /// and a [power cycle nonce](Self::with_power_cycle_nonce) is mixed into every draw.
+    /// None of this needs any device references.
+    ///
+    /// Enabling everything makes creating the RNG and each draw do slightly more work than [`SystemRng::new`],
+    /// and the nonce means the output can't be replayed across boots from a saved seed.
+    /// For more entropy than the system inputs can give, use an [`AdiRng`] with empty ports.
+    pub fn max_entropy() -> SystemRng {
+        Self::new()
+            .with_battery_noise_amplification()
+            .with_device_identity()
+            .with_address_entropy()
+            .with_power_cycle_nonce()
+    }
+
+    /// Check for onboard ADI ports that could be used to strengthen a `SystemRng`.
+    ///
+    /// Every onboard port still in `peripherals` is unclaimed, and a port is reported as free if its reading also jitters
+    /// the way a floating pin does. Passing those ports to [`CombinedRng::standard`] or an [`AdiRng`]
+    /// adds several bits of entropy per draw. Returns `None` if no port looks free.
+    ///
+    /// Each port is read a few times, waiting for the ADI readings to refresh in between,
+    /// so this blocks for about a third of a second. It is meant to be run once while setting up a program.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     if let Some(advice) = SystemRng::advise(&p) {
+    ///         println!("{} free ADI ports could add {} bits per draw", advice.free_port_count(), advice.additional_bits());
+    ///     }
+    /// }
+    /// ```
+    pub fn advise(peripherals: &Peripherals) -> Option<Advice> {
+        let ports = [
+            &peripherals.adi_a,
+            &peripherals.adi_b,
+            &peripherals.adi_c,
+            &peripherals.adi_d,
+            &peripherals.adi_e,
+            &peripherals.adi_f,
+            &peripherals.adi_g,
+            &peripherals.adi_h,
+        ];
+        let mut free_ports = 0;
+        for (index, port) in ports.into_iter().enumerate() {
+            if adi::port_entropy(port, ADVICE_SAMPLES) > 0.0 {
+                free_ports |= 1 << index;
+            }
+        }
+        (free_ports != 0).then_some(Advice { free_ports })
+    }
+
+    /// Mix the address of a stack variable into the state of this RNG.
+    ///
+    /// This is a one-time input that can differentiate otherwise identical boots for free,
+    /// but it only has a few bits of entropy at best.
+    /// Programs are loaded at a fixed address on the V5, so the stack address may stay the same between runs
+    /// and contribute nothing at all. It never makes the output any weaker, though.
+    pub fn with_address_entropy(mut self) -> Self {
+        let marker = 0u8;
+        let address = core::hint::black_box(core::ptr::addr_of!(marker)) as usize;
+        self.state.mix_seed(|hasher| hasher.write_usize(address));
+        self
+    }
+
+    /// Start mixing the given system inputs into every draw.
+    ///
+    /// Every source is enabled by default.
+    pub fn enable_source(&mut self, sources: SourceFlags) {
+        self.state.sources |= sources;
+    }
+
+    /// Stop mixing the given system inputs into every draw.
+    ///
+    /// This is useful for experimenting with the trade-off between entropy and latency,
+    /// or for finding out which input contributes most. The seed and draw counter are always mixed in,
+    /// so output keeps advancing even with every source disabled, but it is no longer random.
+    pub fn disable_source(&mut self, sources: SourceFlags) {
+        self.state.sources = self.state.sources - sources;
+    }
+
+    /// Returns the system inputs currently mixed into every draw.
+    pub fn sources(&self) -> SourceFlags {
+        self.state.sources
+    }
+
+    /// Time how long each enabled system input takes to read, on average over `iterations` reads.
+    ///
+    /// Each item is a single source and its average cost, timed with the high-resolution timer.
+    /// This helps pick which sources to [disable](Self::disable_source) when draws are too slow for a tight loop.
+    /// Without the `battery` feature, the battery is never read and isn't reported.
+    /// With zero iterations, every cost is reported as zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let rng = SystemRng::new();
+    ///     for (source, cost) in rng.profile_sources(1_000) {
+    ///         println!("{source}: {cost:?}");
+    ///     }
+    /// }
+    /// ```
+    pub fn profile_sources(
+        &self,
+        iterations: usize,
+    ) -> impl Iterator<Item = (SourceFlags, Duration)> {
+        let state = self.state;
+        [
+            SourceFlags::BATTERY,
+            SourceFlags::PROGRAM_TIME,
+            SourceFlags::POWERUP_TIME,
+        ]
+        .into_iter()
+        .filter(move |&source| {
+            state.sources.contains(source)
+                && (cfg!(feature = "battery") || source != SourceFlags::BATTERY)
+        })
+        .map(move |source| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                core::hint::black_box(state.read_source(source));
+            }
+            let elapsed = start.elapsed().as_nanos();
+            let cost = match iterations {
+                0 => Duration::ZERO,
+                _ => Duration::from_nanos((elapsed / iterations as u128) as u64),
+            };
+            (source, cost)
+        })
+    }
+
+    /// Pass the raw system metrics read for every draw to `sink`.
+    ///
+    /// The recorded [`RawSample`]s can later be fed into a [`ReplayRng`] to reproduce the exact same output,
+    /// which is useful for dumping a match's randomness to the SD card and debugging it on a computer.
+    /// Only draws made through the returned [`Recording`] are recorded.
+    pub fn with_recorder<F: FnMut(RawSample)>(self, sink: F) -> Recording<F> {
+        Recording::new(self, sink)
+    }
+
+    /// Create `N` independent RNGs derived from this one.
+    ///
+    /// A word is drawn from this RNG and folded into each child's seed along with the child's index,
+    /// so the streams are independent of each other and of the parent, even when they read identical hardware.
+    /// This is convenient for handing an RNG to each of a fixed number of tasks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let [drive, intake, lift] = rng.parallel_streams();
+    /// }
+    /// ```
+    pub fn parallel_streams<const N: usize>(&mut self) -> [SystemRng; N] {
+        let base = self.next_u64();
+        core::array::from_fn(|index| {
+            let mut child = SystemRng {
+                state: self.state,
+                buffer: ByteBuffer::new(),
+            };
+            child.state.mix_seed(|hasher| {
+                hasher.write_u64(base);
+                hasher.write_usize(index);
+            });
+            child
+        })
+    }
+
+    /// Create a single RNG derived from this one, independent of it.
+    ///
+    /// This is the same as taking one stream from [`parallel_streams`](Self::parallel_streams).
+    pub fn fork(&mut self) -> SystemRng {
+        let [child] = self.parallel_streams();
+        child
+    }
+
+    /// Capture the deterministic part of this RNG's state, to [`restore`](Self::restore) later.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let snapshot = rng.snapshot();
+    ///     let first = rng.next_u64();
+    ///     rng.restore(snapshot);
+    ///     // With identical hardware readings, this would draw `first` again.
+    ///     let second = rng.next_u64();
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> RngSnapshot {
+        RngSnapshot {
+            seed: self.state.seed,
+            counter: self.state.counter,
+            last_battery: self.state.last_battery,
+            buffer: self.buffer,
+            trickle: None,
+            noisy_average: None,
+        }
+    }
+
+    /// Return the deterministic part of this RNG's state to a [`snapshot`](Self::snapshot).
+    ///
+    /// Configuration, like the enabled sources, is left as it is.
+    pub fn restore(&mut self, snapshot: RngSnapshot) {
+        self.state.seed = snapshot.seed;
+        self.state.counter = snapshot.counter;
+        self.state.last_battery = snapshot.last_battery;
+        self.buffer = snapshot.buffer;
+    }
+
+    /// Discard all accumulated state and start over as if this RNG had just been created.
+    ///
+    /// The creation instant is re-anchored to now, the stored seed (including anything added by [`merge`](Self::merge))
+    /// is zeroed, the counter is reset, and a warm-up draw is made and discarded.
+    /// The enabled [sources](Self::sources) and other input settings are kept.
+    /// Use this after a suspected entropy compromise, when stirring in more material isn't enough.
+    pub fn reset_to_fresh(&mut self) {
+        self.state.reset();
+        self.buffer = ByteBuffer::new();
+        self.hash_value();
+    }
+
+    /// Turn this RNG into a fast ChaCha20 generator that transparently reseeds itself from this RNG.
+    ///
+    /// The ChaCha20 core is seeded from this RNG immediately, and again every time `threshold` bytes have been generated.
+    /// This gives high throughput while still refreshing the output with hardware entropy regularly,
+    /// and is the recommended choice for most programs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new().into_reseeding_chacha(64 * 1024);
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    #[cfg(feature = "crypto")]
+    pub fn into_reseeding_chacha(self, threshold: usize) -> ReseedingRng<ChaCha20Core, Self> {
+        ReseedingRng::new(threshold as u64, self).unwrap_or_else(|never| match never {})
+    }
+
+    /// Returns the version of the hashing used by this build of veranda, [`HASH_VERSION`].
+    ///
+    /// Store this alongside saved seeds and recorded outputs to detect ones made by an incompatible version.
+    pub const fn hash_version() -> u32 {
+        HASH_VERSION
+    }
+
+    /// Produce a 256-bit seed from this RNG and several independent `sources`, refusing to if any of them is dead.
+    ///
+    /// Every source is sampled a few times first, and one that writes the same thing every time is rejected,
+    /// as is this RNG if its system metrics read the same every time. Only once everything passes is this RNG
+    /// sampled and each source sampled again into a single seed, so no one dead source can quietly weaken it.
+    /// Like [`finalize_256`](Self::finalize_256), the seed contains no more entropy than one sample of every input.
+    ///
+    /// # Errors
+    ///
+    /// - An [`InitError::StuckSource`] error is returned for the first source that is stuck.
+    /// - An [`InitError::SelfTestFailed`] error is returned if this RNG's system metrics never changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::{AdiEdgeSource, CurrentJitterSource, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     let mut edges = AdiEdgeSource::new(&p.adi_a);
+    ///     let mut current = CurrentJitterSource::new();
+    ///     match rng.robust_seed(&mut [&mut edges, &mut current]) {
+    ///         Ok(seed) => println!("Seeded from every source"),
+    ///         Err(error) => println!("Refusing to seed: {error}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn robust_seed(
+        &mut self,
+        sources: &mut [&mut dyn EntropySource],
+    ) -> Result<[u8; 32], InitError> {
+        if let Some(index) = sources
+            .iter_mut()
+            .position(|source| health::source_is_stuck(&mut **source))
+        {
+            return Err(InitError::StuckSource { index });
+        }
+        if health::inputs_stuck(&mut self.state) {
+            return Err(InitError::SelfTestFailed);
+        }
+        let mut hasher = self.accumulate();
+        for source in sources {
+            source.sample(&mut hasher);
+        }
+        Ok(squeeze_256(&hasher))
+    }
+
+    /// Returns a rough estimate of the entropy, in bits, contributed to each draw.
+    ///
+    /// Disabling system inputs with [`disable_source`](Self::disable_source) lowers the estimate,
+    /// and [battery noise amplification](Self::with_battery_noise_amplification) raises it.
+    pub fn estimated_entropy_bits(&self) -> f32 {
+        self.state.system_entropy_bits()
+    }
+
+    /// Returns the current health of this RNG.
+    ///
+    /// The only problem a `SystemRng` can detect is its inputs collapsing entirely,
+    /// which is reported as [`EntropyWarning::StuckTimers`], or as [`EntropyWarning::RepeatedOutput`]
+    /// if resampling is enabled and couldn't avoid a repeat.
+    pub fn status(&self) -> EntropyStatus {
+        EntropyStatus {
+            estimated_bits: self.estimated_entropy_bits(),
+            warning: if self.state.timers_stuck() {
+                Some(EntropyWarning::StuckTimers)
+            } else if self.state.repeated_output {
+                Some(EntropyWarning::RepeatedOutput)
+            } else {
+                None
+            },
+            draw_count: self.draw_count(),
+            bytes_generated: self.bytes_generated(),
         }
     }
 
-    fn hash_value(&self) -> u64 {
-        let mut hasher = BuildHasherDefault::<AHasher>::default().build_hasher();
-        hasher.write_u32((battery::voltage() * 1000.0) as _);
-        hasher.write_u32((battery::current() * 1000.0) as _);
-        hasher.write_u128(self.time_of_creation.elapsed().as_micros());
-        hasher.write_u64(unsafe { vexSystemPowerupTimeGet() });
-        hasher.finish()
+    /// Print a human-readable health report to the console.
+    ///
+    /// The report lists the enabled sources, the estimated entropy and [`status`](Self::status),
+    /// then runs the startup self-test and a short benchmark and reports their results and how long they took.
+    /// This is meant to be called once at the start of a program.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use veranda::SystemRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = SystemRng::new();
+    ///     rng.print_startup_report();
+    /// }
+    /// ```
+    pub fn print_startup_report(&mut self) {
+        // The console never fails to write.
+        let _ = self.write_startup_report(&mut diagnostics::Console);
+    }
+
+    /// Write the report printed by [`print_startup_report`](Self::print_startup_report) to `out`.
+    fn write_startup_report(&mut self, out: &mut impl fmt::Write) -> fmt::Result {
+        let status = self.status();
+        let sources = self.sources();
+        diagnostics::write_startup_report(
+            out,
+            self,
+            |rng| !health::inputs_stuck(&mut rng.state),
+            format_args!("{sources}"),
+            status,
+        )
+    }
+
+    /// Move this RNG into a boxed trait object.
+    ///
+    /// `SystemRng` owns all of its state, so it can always be boxed.
+    /// This is useful for selecting an RNG at runtime in plugin-style code.
+    #[cfg(feature = "alloc")]
+    pub fn boxed(self) -> Box<dyn RngCore + Send> {
+        Box::new(self)
+    }
+
+    fn accumulate(&mut self) -> AHasher {
+        let mut hasher = hasher();
+        self.state.write_inputs(&mut hasher);
+        hasher
+    }
+
+    /// Draw like [`hash_value`](Self::hash_value), passing the system metrics that were read to `sink`.
+    fn recorded_hash_value(&mut self, sink: &mut impl FnMut(RawSample)) -> u64 {
+        let sample = self.state.read_sample();
+        sink(sample);
+        let mut hasher = hasher();
+        self.state.write_sample(&mut hasher, &sample);
+        let value = hasher.finish();
+        self.record_health();
+        value
     }
 }
 impl Default for SystemRng {
@@ -63,36 +737,38 @@ impl Default for SystemRng {
     }
 }
 
-impl RngCore for SystemRng {
-    fn next_u32(&mut self) -> u32 {
-        self.hash_value() as u32
-    }
-
-    fn next_u64(&mut self) -> u64 {
-        self.hash_value()
-    }
+impl_hardware_rng!(SystemRng);
 
-    fn fill_bytes(&mut self, dest: &mut [u8]) {
-        dest.chunks_mut(4)
-            .map(|chunk| {
-                let len = chunk.len();
-                let value = self.hash_value();
-                chunk.copy_from_slice(&value.to_le_bytes()[..len]);
-            })
-            .count();
+/// Reading from a `SystemRng` fills the entire buffer with random bytes and never fails.
+///
+/// Bytes are served from the same buffer as [`SystemRng::next_u8`].
+impl io::Read for SystemRng {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill_buffered(buf);
+        Ok(buf.len())
     }
 }
 
 /// A [`rand`](https://crates.io/crates/rand) RNG source that includes empty ADI port(s) as a source of entropy.
 /// It is incredibly important that the port is not connected to anything, as this will cause the RNG to be predictable.
+///
+/// A single port contributes much less entropy than several.
+/// Check [`AdiRng::status`] at startup to catch weak configurations.
+///
+/// `AdiRng` is `Send` and `Sync`, but it borrows its ports.
+/// To move one into a spawned task, the ports must live for `'static`, for example by storing them in a `static`.
 #[derive(Debug, Eq, PartialEq)]
 pub struct AdiRng<'a> {
     ports: &'a [AdiPort],
-    time_of_creation: Instant,
+    noisy_device: Option<NoisyDevice<'a>>,
+    trickle: Option<Trickle>,
+    state: State,
+    buffer: ByteBuffer,
 }
-impl AdiRng<'_> {
+impl<'a> AdiRng<'a> {
     /// Create a new `AdiRng` with the given ADI ports.
     /// Passing in multiple ports will increase the entropy of the RNG.
+    /// The ports may be spread across the brain and any number of ADI expanders.
     ///
     /// # Examples
     ///
@@ -114,50 +790,903 @@ impl AdiRng<'_> {
     ///     }
     /// }
     /// ```
-    pub fn new(ports: &[AdiPort]) -> AdiRng {
+    pub fn new(ports: &'a [AdiPort]) -> AdiRng<'a> {
         AdiRng {
             ports,
-            time_of_creation: Instant::now(),
+            noisy_device: None,
+            trickle: None,
+            state: State::new(),
+            buffer: ByteBuffer::new(),
+        }
+    }
+
+    /// Create a new `AdiRng` with the given ADI ports, checking that no port is passed twice.
+    ///
+    /// Passing the same port twice would count it twice in the [entropy estimate](AdiRng::estimated_entropy_bits)
+    /// without adding any entropy. Ports are compared by their expander and port number.
+    ///
+    /// # Errors
+    ///
+    /// - An [`AdiRngError::DuplicatePort`] error is returned if any port appears more than once.
+    pub fn try_new(ports: &'a [AdiPort]) -> Result<AdiRng<'a>, AdiRngError> {
+        for (index, port) in ports.iter().enumerate() {
+            let id = adi::port_id(port);
+            if ports[..index].iter().any(|other| adi::port_id(other) == id) {
+                return Err(AdiRngError::DuplicatePort {
+                    expander_number: id.0,
+                    number: id.1,
+                });
+            }
         }
+        Ok(Self::new(ports))
+    }
+
+    /// Create a new `AdiRng` that only reads from a single ADI port.
+    ///
+    /// A single port only adds a few bits of entropy on top of the system metrics used by [`SystemRng`],
+    /// so prefer [`AdiRng::new`] with several empty ports whenever they are available.
+    /// The [`status`](AdiRng::status) of an RNG created this way will always report [`EntropyWarning::SinglePort`].
+    pub fn single(port: &'a AdiPort) -> AdiRng<'a> {
+        Self::new(core::slice::from_ref(port))
+    }
+
+    /// Also read from an analog device that is connected, but noisy.
+    ///
+    /// Unlike the empty ports passed to [`AdiRng::new`], this port may have a device like a potentiometer or line tracker plugged in.
+    /// As long as the device isn't outputting a fixed voltage, the lowest bits of its readings jitter and can still contribute a little entropy.
+    /// Readings are debiased by subtracting a running average, so only the jitter is mixed in rather than the device's position.
+    ///
+    /// If the device's reading stops changing, [`status`](AdiRng::status) reports [`EntropyWarning::ConstantDevice`]
+    /// and it no longer counts towards the entropy estimate.
+    /// Calling this again replaces the previously configured device.
+    pub fn with_noisy_device(mut self, port: &'a AdiPort) -> Self {
+        self.noisy_device = Some(NoisyDevice::new(port));
+        self
     }
-    fn hash_value(&self) -> u64 {
-        let values = self.ports.iter().map(|port| unsafe {
-            vexDeviceAdiValueGet(
-                vexDeviceGetByIndex(port.expander_number().unwrap_or(21) as _),
-                port.number() as _,
-            )
+
+    /// Replace the empty ports this RNG reads from.
+    ///
+    /// This is useful when a program reconfigures which ports are free while it runs.
+    /// A fresh sample of the new ports is folded into the stored seed, and the
+    /// [entropy estimate](AdiRng::estimated_entropy_bits) follows the new port count.
+    /// The noisy device, if any, is kept.
+    pub fn refresh_ports(&mut self, ports: &'a [AdiPort]) {
+        self.ports = ports;
+        self.state.mix_seed(|hasher| {
+            for port in ports {
+                Self::sample_port(hasher, port);
+            }
         });
+    }
+
+    /// Returns the strongest correlation between the readings of any two configured ports, from `0.0` to `1.0`.
+    ///
+    /// Two "empty" ports that are physically coupled (for example, adjacent floating pins picking up the same noise)
+    /// don't contribute independent entropy, so the [entropy estimate](AdiRng::estimated_entropy_bits)
+    /// overcounts them. A result near `1.0` means that at least one pair of ports is coupled,
+    /// and only one of them should be used. Independent ports report a value near `0.0`.
+    ///
+    /// Each pair of ports is read `samples` times, waiting for the ADI readings to refresh between reads,
+    /// so this blocks for about `10 ms × samples × pairs`. It is meant to be run once while choosing ports,
+    /// not during a match. With fewer than two ports, this returns `0.0`.
+    pub fn port_correlation(&mut self, samples: usize) -> f32 {
+        let mut worst = 0.0f32;
+        for (index, a) in self.ports.iter().enumerate() {
+            for b in &self.ports[index + 1..] {
+                worst = worst.max(adi::correlation(a, b, samples).abs());
+            }
+        }
+        worst
+    }
+
+    /// Estimate how much entropy each configured port contributes, yielding `(port_number, estimated_bits)` pairs.
+    ///
+    /// Each port is read `samples` times, and its entropy per reading is estimated from how much the readings spread out.
+    /// A port reporting close to `0.0` bits is stuck or connected to something, and is only slowing down draws,
+    /// so it's a good candidate to drop. This is more actionable than the fixed per-port
+    /// [estimate](AdiRng::estimated_entropy_bits) when tuning which ports to use.
+    ///
+    /// Ports are measured lazily as the iterator is advanced, waiting for the ADI readings to refresh between reads,
+    /// so each port blocks for about `10 ms × samples`. Like [`port_correlation`](Self::port_correlation),
+    /// this is meant to be run while choosing ports, not during a match.
+    pub fn entropy_per_port(&mut self, samples: usize) -> impl Iterator<Item = (u8, f32)> + 'a {
+        self.ports
+            .iter()
+            .map(move |port| (port.number(), adi::port_entropy(port, samples)))
+    }
+
+    /// Spread sampling out by reading only one ADI source per draw.
+    ///
+    /// Normally every port (and the noisy device, if any) is read on every draw, which can cause a noticeable
+    /// latency spike in tight control loops when many ports are configured.
+    /// In trickle mode, each draw reads a single source, cycling through them in order,
+    /// and the hash of every previous draw is chained into the next so that entropy still accumulates over time.
+    /// The system metrics are still read on every draw.
+    ///
+    /// This trades a little per-draw freshness for a much lower cost per call:
+    /// it takes a full cycle of draws before every source has contributed again.
+    pub fn with_trickle(mut self) -> Self {
+        self.trickle = Some(Trickle::new());
+        self
+    }
+
+    /// Capture the deterministic part of this RNG's state, to [`restore`](Self::restore) later.
+    ///
+    /// In trickle mode, this includes the chained hash and the position in the cycle of sources.
+    pub fn snapshot(&self) -> RngSnapshot {
+        RngSnapshot {
+            seed: self.state.seed,
+            counter: self.state.counter,
+            last_battery: self.state.last_battery,
+            buffer: self.buffer,
+            trickle: self.trickle,
+            noisy_average: self.noisy_device.as_ref().map(NoisyDevice::average),
+        }
+    }
+
+    /// Return the deterministic part of this RNG's state to a [`snapshot`](Self::snapshot).
+    ///
+    /// Configuration, like the ports, is left as it is. The trickle state is only restored
+    /// if this RNG and the snapshot are both in trickle mode, and the noisy device's average
+    /// only if both have a noisy device.
+    pub fn restore(&mut self, snapshot: RngSnapshot) {
+        self.state.seed = snapshot.seed;
+        self.state.counter = snapshot.counter;
+        self.state.last_battery = snapshot.last_battery;
+        self.buffer = snapshot.buffer;
+        if let (Some(trickle), Some(saved)) = (&mut self.trickle, snapshot.trickle) {
+            *trickle = saved;
+        }
+        if let (Some(device), Some(average)) = (&mut self.noisy_device, snapshot.noisy_average) {
+            device.set_average(average);
+        }
+    }
+
+    /// Discard all accumulated state and start over as if this RNG had just been created.
+    ///
+    /// The creation instant is re-anchored to now, the stored seed (including anything added by [`merge`](Self::merge))
+    /// is zeroed, the counter is reset, and a warm-up draw is made and discarded.
+    /// Use this after a suspected entropy compromise, when stirring in more material isn't enough.
+    pub fn reset_to_fresh(&mut self) {
+        self.state.reset();
+        self.buffer = ByteBuffer::new();
+        if let Some(trickle) = &mut self.trickle {
+            *trickle = Trickle::new();
+        }
+        self.hash_value();
+    }
+
+    /// Turn this RNG into a fast ChaCha20 generator that transparently reseeds itself from this RNG.
+    ///
+    /// The ChaCha20 core is seeded from this RNG immediately, and again every time `threshold` bytes have been generated.
+    /// This gives high throughput while still refreshing the output with hardware entropy regularly,
+    /// and is the recommended choice for most programs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::AdiRng;
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let ports = &[p.adi_a, p.adi_b];
+    ///     let mut rng = AdiRng::new(ports).into_reseeding_chacha(64 * 1024);
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    #[cfg(feature = "crypto")]
+    pub fn into_reseeding_chacha(self, threshold: usize) -> ReseedingRng<ChaCha20Core, Self> {
+        ReseedingRng::new(threshold as u64, self).unwrap_or_else(|never| match never {})
+    }
+
+    /// Returns a rough estimate of the entropy, in bits, contributed to each draw.
+    ///
+    /// Each port adds to the estimate, so configurations with more empty ports score higher.
+    /// In [trickle mode](Self::with_trickle), the ports' contribution is averaged over a full cycle of draws.
+    pub fn estimated_entropy_bits(&self) -> f32 {
+        let noisy_bits = match &self.noisy_device {
+            Some(device) if !device.is_constant() => NOISY_DEVICE_ENTROPY_BITS,
+            _ => 0.0,
+        };
+        let adi_bits = self.ports.len() as f32 * ADI_PORT_ENTROPY_BITS + noisy_bits;
+        match self.trickle {
+            Some(_) => SYSTEM_ENTROPY_BITS + adi_bits / self.source_count().max(1) as f32,
+            None => SYSTEM_ENTROPY_BITS + adi_bits,
+        }
+    }
+
+    /// Returns the current health of this RNG.
+    ///
+    /// Configurations with fewer than two ports (counting a noisy device) are reported with a warning,
+    /// since they are barely stronger than a [`SystemRng`].
+    /// Stuck timers take priority over every other warning, followed by a repeated output.
+    pub fn status(&self) -> EntropyStatus {
+        let warning = if self.state.timers_stuck() {
+            Some(EntropyWarning::StuckTimers)
+        } else if self.state.repeated_output {
+            Some(EntropyWarning::RepeatedOutput)
+        } else if self
+            .noisy_device
+            .as_ref()
+            .is_some_and(NoisyDevice::is_constant)
+        {
+            Some(EntropyWarning::ConstantDevice)
+        } else {
+            match self.source_count() {
+                0 => Some(EntropyWarning::NoPorts),
+                1 => Some(EntropyWarning::SinglePort),
+                _ => None,
+            }
+        };
+        EntropyStatus {
+            estimated_bits: self.estimated_entropy_bits(),
+            warning,
+            draw_count: self.draw_count(),
+            bytes_generated: self.bytes_generated(),
+        }
+    }
+
+    /// Print a human-readable health report to the console.
+    ///
+    /// The report lists the ADI ports in use, the estimated entropy and [`status`](Self::status),
+    /// then runs the startup self-test and a short benchmark and reports their results and how long they took.
+    /// This is meant to be called once at the start of a program.
+    pub fn print_startup_report(&mut self) {
+        // The console never fails to write.
+        let _ = self.write_startup_report(&mut diagnostics::Console);
+    }
 
-        let mut hasher = BuildHasherDefault::<AHasher>::default().build_hasher();
+    /// Write the report printed by [`print_startup_report`](Self::print_startup_report) to `out`.
+    fn write_startup_report(&mut self, out: &mut impl fmt::Write) -> fmt::Result {
+        let status = self.status();
+        let ports = self.ports.len();
+        let noisy_device = if self.noisy_device.is_some() {
+            " and a noisy device"
+        } else {
+            ""
+        };
+        diagnostics::write_startup_report(
+            out,
+            self,
+            |rng| !health::inputs_stuck(&mut rng.state),
+            format_args!("system metrics, {ports} ADI ports{noisy_device}"),
+            status,
+        )
+    }
+
+    /// Returns the number of ADI sources, counting the noisy device.
+    fn source_count(&self) -> usize {
+        self.ports.len() + usize::from(self.noisy_device.is_some())
+    }
+
+    fn sample_port(hasher: &mut AHasher, port: &AdiPort) {
+        adi::write_port_id(hasher, port);
+        hasher.write_i32(backend::adi_value(port));
+    }
+
+    fn accumulate(&mut self) -> AHasher {
+        let mut hasher = hasher();
 
-        for value in values {
-            hasher.write_i32(value);
+        let count = self.source_count();
+        if let Some(trickle) = &mut self.trickle {
+            hasher.write_u64(trickle.chain);
+            if count > 0 {
+                match self.ports.get(trickle.advance(count)) {
+                    Some(port) => Self::sample_port(&mut hasher, port),
+                    None => {
+                        if let Some(device) = &mut self.noisy_device {
+                            device.sample(&mut hasher);
+                        }
+                    }
+                }
+            }
+            self.state.write_inputs(&mut hasher);
+
+            // Chain a separate hash forward so that the next draw doesn't start from this draw's output.
+            let mut chain = hasher.clone();
+            chain.write_u8(0);
+            trickle.chain = chain.finish();
+            return hasher;
+        }
+
+        for port in self.ports {
+            Self::sample_port(&mut hasher, port);
         }
-        hasher.write_u32((battery::voltage() * 1000.0) as _);
-        hasher.write_u32((battery::current() * 1000.0) as _);
-        hasher.write_u128(self.time_of_creation.elapsed().as_micros());
-        hasher.write_u64(unsafe { vexSystemPowerupTimeGet() });
+        if let Some(device) = &mut self.noisy_device {
+            device.sample(&mut hasher);
+        }
+        self.state.write_inputs(&mut hasher);
+
+        hasher
+    }
+}
+
+impl_hardware_rng!(AdiRng<'_>);
+
+impl AdiRng<'static> {
+    /// Move this RNG into a boxed trait object.
+    ///
+    /// An `AdiRng` borrows its ports, so it can only be boxed when the ports live for `'static`,
+    /// for example by storing them in a `static`.
+    #[cfg(feature = "alloc")]
+    pub fn boxed(self) -> Box<dyn RngCore + Send> {
+        Box::new(self)
+    }
+}
 
-        hasher.finish()
+/// Reading from an `AdiRng` fills the entire buffer with random bytes and never fails.
+///
+/// Bytes are served from the same buffer as [`AdiRng::next_u8`].
+impl io::Read for AdiRng<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill_buffered(buf);
+        Ok(buf.len())
     }
 }
 
-impl RngCore for AdiRng<'_> {
-    fn next_u32(&mut self) -> u32 {
-        self.hash_value() as u32
+#[cfg(test)]
+mod tests {
+    use core::{
+        hash::Hasher,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    use rand::{distr::Uniform, RngCore};
+
+    use std::{
+        boxed::Box, cell::RefCell, format, rc::Rc, string::String, string::ToString, vec::Vec,
+    };
+
+    use crate::{
+        backend::mock,
+        health::{EntropyWarning, InitError, ADI_PORT_ENTROPY_BITS, NOISY_DEVICE_ENTROPY_BITS},
+        io,
+        source::{EntropySource, SourceFlags},
+        AdiRng, EnvironmentProfile, SystemRng,
+    };
+
+    /// A source that writes the same value on every sample.
+    struct Constant;
+
+    impl EntropySource for Constant {
+        fn sample(&mut self, hasher: &mut dyn Hasher) {
+            hasher.write_u64(7);
+        }
+    }
+
+    /// A source that writes a different value on every sample.
+    struct Counting(u64);
+
+    impl EntropySource for Counting {
+        fn sample(&mut self, hasher: &mut dyn Hasher) {
+            self.0 += 1;
+            hasher.write_u64(self.0);
+        }
+    }
+
+    #[test]
+    fn system_rng_runs_and_varies_on_host() {
+        let mut rng = SystemRng::new();
+        let first = rng.next_u64();
+        assert!((0..8).any(|_| rng.next_u64() != first));
+    }
+
+    #[test]
+    fn robust_seed_accepts_healthy_sources() {
+        let mut rng = SystemRng::new();
+        let seed = rng.robust_seed(&mut [&mut Counting(0), &mut Counting(100), &mut Counting(200)]);
+        assert!(seed.is_ok());
+    }
+
+    #[test]
+    fn robust_seed_rejects_a_stuck_source() {
+        let mut rng = SystemRng::new();
+        let seed = rng.robust_seed(&mut [&mut Counting(0), &mut Constant, &mut Counting(100)]);
+        assert_eq!(seed, Err(InitError::StuckSource { index: 1 }));
+    }
+
+    // The deterministic readings replace the frozen mock, so the timers never stall.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn robust_seed_rejects_stuck_system_metrics() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let seed = rng.robust_seed(&mut [&mut Counting(0)]);
+        assert_eq!(seed, Err(InitError::SelfTestFailed));
+    }
+
+    #[test]
+    fn startup_report_has_every_section() {
+        let mut report = String::new();
+        SystemRng::new().write_startup_report(&mut report).unwrap();
+        for section in [
+            "sources: battery, program time, powerup time",
+            "estimated entropy: ",
+            "health: ok",
+            "self-test: passed",
+            "benchmark: ",
+            "time taken: ",
+        ] {
+            assert!(
+                report.contains(section),
+                "{section:?} missing from {report}"
+            );
+        }
+    }
+
+    // The deterministic readings replace the frozen mock, so the timers never stall.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn startup_report_shows_a_failed_self_test() {
+        // The timer has to keep advancing for the benchmark to finish, so the program time is left out instead.
+        mock::install(mock::Mock {
+            time_step: 1_000,
+            ..mock::Mock::frozen()
+        });
+        let mut rng = SystemRng::new();
+        rng.disable_source(SourceFlags::PROGRAM_TIME);
+        let mut report = String::new();
+        rng.write_startup_report(&mut report).unwrap();
+        assert!(report.contains("self-test: failed"), "{report}");
+    }
+
+    #[test]
+    fn debug_output_hides_the_seed_and_buffered_bytes() {
+        let mut rng = SystemRng::with_seed_bytes(b"team 1234").with_power_cycle_nonce();
+        rng.next_u8();
+        let debug = format!("{rng:?}");
+        for secret in [rng.state.seed, rng.state.boot_nonce.unwrap()] {
+            assert!(!debug.contains(&secret.to_string()), "{debug}");
+        }
+        for field in ["seed", "nonce", "bytes", "last_output"] {
+            assert!(!debug.contains(field), "{debug}");
+        }
+    }
+
+    #[test]
+    fn restoring_a_snapshot_replays_the_same_draws() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let snapshot = rng.snapshot();
+        let first: [u64; 4] = core::array::from_fn(|_| rng.next_u64());
+        rng.restore(snapshot);
+        let second: [u64; 4] = core::array::from_fn(|_| rng.next_u64());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn restoring_a_snapshot_replays_the_noisy_device() {
+        mock::install(mock::Mock::frozen());
+        let ports = mock::ports::<3>();
+        let mut rng = AdiRng::new(&ports[..2]).with_noisy_device(&ports[2]);
+        // The device settles far from its first reading, so its average moves on every draw.
+        mock::with(|mock| mock.adi = Some(Box::new(|_| 400)));
+
+        rng.next_u64();
+        let snapshot = rng.snapshot();
+        let first: [u64; 4] = core::array::from_fn(|_| rng.next_u64());
+        rng.restore(snapshot);
+        let second: [u64; 4] = core::array::from_fn(|_| rng.next_u64());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn a_single_port_is_flagged_as_weak() {
+        let ports = mock::ports::<3>();
+        let one = AdiRng::new(&ports[..1]);
+        let three = AdiRng::new(&ports);
+        assert_eq!(one.status().warning, Some(EntropyWarning::SinglePort));
+        assert!(three.status().is_healthy());
+        assert_eq!(
+            three.estimated_entropy_bits() - one.estimated_entropy_bits(),
+            2.0 * ADI_PORT_ENTROPY_BITS
+        );
+    }
+
+    #[test]
+    fn the_stack_address_is_mixed_in() {
+        mock::install(mock::Mock::frozen());
+        let plain = SystemRng::new().next_u64();
+        assert_eq!(SystemRng::new().next_u64(), plain);
+        assert_ne!(SystemRng::new().with_address_entropy().next_u64(), plain);
+    }
+
+    #[test]
+    fn reading_fills_buffers_of_any_size() {
+        let mut rng = SystemRng::new();
+        for len in [1, 3, 8, 13, 64] {
+            let mut buf = [0; 64];
+            assert_eq!(io::Read::read(&mut rng, &mut buf[..len]).unwrap(), len);
+            if len >= 8 {
+                assert!(buf[..len].iter().any(|&byte| byte != buf[0]));
+            }
+        }
+    }
+
+    #[cfg(not(feature = "battery"))]
+    #[test]
+    fn time_inputs_alone_vary_the_output() {
+        mock::install(mock::Mock {
+            time_step: 1,
+            ..mock::Mock::frozen()
+        });
+        let mut rng = SystemRng::new();
+        let first = rng.next_u64();
+        assert!((0..8).all(|_| rng.next_u64() != first));
+        let calls = mock::calls();
+        assert_eq!(calls.battery_voltage + calls.battery_current, 0);
+    }
+
+    #[test]
+    fn rngs_can_move_to_another_thread() {
+        let ports: &'static [_] = Box::leak(Box::new(mock::ports::<2>()));
+        let mut system = SystemRng::new();
+        let mut adi = AdiRng::new(ports);
+        let handle = std::thread::spawn(move || (system.next_u64(), adi.next_u64()));
+        assert!(handle.join().is_ok());
+    }
+
+    #[test]
+    fn a_noisy_device_is_credited_until_it_stops_changing() {
+        mock::install(mock::Mock {
+            time_step: 100_000,
+            ..mock::Mock::frozen()
+        });
+        let mut reading = 0;
+        mock::with(|mock| {
+            mock.adi = Some(Box::new(move |_| {
+                reading += 7;
+                reading
+            }))
+        });
+        let ports = mock::ports::<3>();
+        let mut rng = AdiRng::new(&ports[..2]).with_noisy_device(&ports[2]);
+        let quiet = AdiRng::new(&ports[..2]).estimated_entropy_bits();
+        for _ in 0..10 {
+            rng.next_u64();
+        }
+        assert_eq!(
+            rng.estimated_entropy_bits(),
+            quiet + NOISY_DEVICE_ENTROPY_BITS
+        );
+        assert!(rng.status().is_healthy());
+
+        mock::with(|mock| mock.adi = Some(Box::new(|_| 512)));
+        for _ in 0..10 {
+            rng.next_u64();
+        }
+        assert_eq!(rng.estimated_entropy_bits(), quiet);
+        assert_eq!(rng.status().warning, Some(EntropyWarning::ConstantDevice));
+    }
+
+    #[test]
+    fn different_seed_bytes_diverge() {
+        mock::install(mock::Mock::frozen());
+        let draws = |mut rng: SystemRng| core::array::from_fn::<u64, 4, _>(|_| rng.next_u64());
+        let team = draws(SystemRng::with_seed_bytes(b"team 1234"));
+        assert_eq!(draws(SystemRng::with_seed_bytes(b"team 1234")), team);
+        assert_ne!(draws(SystemRng::with_seed_bytes(b"team 1235")), team);
+        assert_ne!(draws(SystemRng::new()), team);
     }
 
-    fn next_u64(&mut self) -> u64 {
-        self.hash_value()
+    #[test]
+    fn different_byte_iterators_diverge() {
+        mock::install(mock::Mock::frozen());
+        let first = |iter: std::ops::Range<u8>| SystemRng::from_byte_iter(iter).next_u64();
+        assert_eq!(first(0..16), first(0..16));
+        assert_ne!(first(0..16), first(1..17));
+        assert_eq!(
+            first(0..16),
+            SystemRng::from_byte_iter((0..16).collect::<Vec<_>>()).next_u64()
+        );
+    }
+
+    #[test]
+    fn reset_anchors_the_stream_to_the_new_instant() {
+        mock::install(mock::Mock::frozen());
+        let draws = |rng: &mut SystemRng| core::array::from_fn::<u64, 4, _>(|_| rng.next_u64());
+        let mut rng = SystemRng::with_seed_bytes(b"old state");
+        let before = draws(&mut rng.clone());
+
+        mock::with(|mock| mock.time = Some(9_000_000));
+        rng.reset_to_fresh();
+        let after = draws(&mut rng);
+        assert!(after.iter().all(|value| !before.contains(value)));
+
+        // A reset RNG matches one created at the same instant, after its discarded warm-up draw.
+        let mut fresh = SystemRng::new();
+        fresh.next_u64();
+        assert_eq!(after, draws(&mut fresh));
+    }
+
+    #[test]
+    fn trickle_mode_visits_every_port_in_turn() {
+        let read: Rc<RefCell<Vec<u8>>> = Rc::default();
+        let log = Rc::clone(&read);
+        mock::with(|mock| {
+            mock.adi = Some(Box::new(move |port| {
+                log.borrow_mut().push(port.number());
+                0
+            }))
+        });
+        let ports = mock::ports::<3>();
+        let mut rng = AdiRng::new(&ports).with_trickle();
+        read.borrow_mut().clear();
+
+        let draws: [u64; 6] = core::array::from_fn(|_| rng.next_u64());
+        assert_eq!(*read.borrow(), [1, 2, 3, 1, 2, 3]);
+        assert!((1..6).all(|i| !draws[..i].contains(&draws[i])));
+    }
+
+    #[test]
+    fn refreshing_ports_changes_the_stream_and_estimate() {
+        mock::install(mock::Mock::frozen());
+        let ports = mock::ports::<3>();
+        let mut refreshed = AdiRng::new(&ports[..1]);
+        let mut unchanged = AdiRng::new(&ports[..1]);
+
+        refreshed.refresh_ports(&ports);
+        assert_ne!(refreshed.next_u64(), unchanged.next_u64());
+        assert_eq!(
+            refreshed.estimated_entropy_bits(),
+            AdiRng::new(&ports).estimated_entropy_bits()
+        );
+        assert!(refreshed.status().is_healthy());
+    }
+
+    // The deterministic readings replace the frozen mock, so the timers never stall.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn a_total_collapse_still_advances_but_is_reported() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let mut previous = rng.next_u64();
+        for _ in 0..2000 {
+            let value = rng.next_u64();
+            assert_ne!(value, previous);
+            previous = value;
+        }
+        assert_eq!(rng.status().warning, Some(EntropyWarning::StuckTimers));
+    }
+
+    #[test]
+    fn sampling_a_uniform_distribution_stays_in_range() {
+        let mut rng = SystemRng::new();
+        let distribution = Uniform::new_inclusive(10u32, 20).unwrap();
+        let samples: Vec<u32> = (0..200).map(|_| rng.sample(distribution)).collect();
+        assert!(samples.iter().all(|sample| (10..=20).contains(sample)));
+        assert!(samples.iter().any(|&sample| sample != samples[0]));
+    }
+
+    #[test]
+    fn disabling_every_source_still_advances() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        rng.disable_source(SourceFlags::ALL);
+        let draws: Vec<u64> = (0..8).map(|_| rng.next_u64()).collect();
+        assert!(draws.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn disabling_the_battery_removes_its_influence() {
+        let first_draw = |battery, enabled: bool| {
+            mock::install(mock::Mock {
+                battery,
+                ..mock::Mock::frozen()
+            });
+            let mut rng = SystemRng::new();
+            if !enabled {
+                rng.disable_source(SourceFlags::BATTERY);
+            }
+            rng.next_u64()
+        };
+        assert_eq!(
+            first_draw((12.0, 1.0), false),
+            first_draw((13.0, 2.0), false)
+        );
+        // The deterministic readings replace the mocked battery.
+        if cfg!(all(feature = "battery", not(feature = "deterministic"))) {
+            assert_ne!(first_draw((12.0, 1.0), true), first_draw((13.0, 2.0), true));
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn reseeding_chacha_only_hashes_at_the_threshold() {
+        let mut rng = SystemRng::new().into_reseeding_chacha(1024);
+        let mut bytes = [0; 512];
+        rng.fill_bytes(&mut bytes);
+        let seeded = mock::calls().powerup_time;
+        assert!(seeded > 0);
+        let mut more = [0; 512];
+        rng.fill_bytes(&mut more);
+        assert_ne!(bytes, more);
+        assert_eq!(mock::calls().powerup_time, seeded);
+        for _ in 0..4 {
+            rng.fill_bytes(&mut more);
+        }
+        assert!(mock::calls().powerup_time > seeded);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn boxed_rngs_draw_through_the_trait_object() {
+        let ports: &'static [_] = Box::leak(Box::new(mock::ports::<2>()));
+        for mut rng in [SystemRng::new().boxed(), AdiRng::new(ports).boxed()] {
+            let first = rng.next_u64();
+            assert_ne!(rng.next_u64(), first);
+            let mut bytes = [0; 16];
+            rng.fill_bytes(&mut bytes);
+            assert!(bytes.iter().any(|&byte| byte != bytes[0]));
+        }
+    }
+
+    #[test]
+    fn max_entropy_reads_more_and_estimates_higher() {
+        let reads = |rng: fn() -> SystemRng| {
+            mock::install(mock::Mock::default());
+            let mut rng = rng();
+            rng.next_u64();
+            (mock::calls(), rng.estimated_entropy_bits())
+        };
+        let (default_calls, default_bits) = reads(SystemRng::new);
+        let (max_calls, max_bits) = reads(SystemRng::max_entropy);
+        assert!(max_calls.powerup_time > default_calls.powerup_time);
+        assert!(max_calls.high_res_time > default_calls.high_res_time);
+        if cfg!(feature = "battery") {
+            assert!(max_calls.hardware_reads() > default_calls.hardware_reads());
+            assert!(max_bits > default_bits);
+        }
+    }
+
+    #[test]
+    fn uninit_anchors_the_instant_on_the_first_draw() {
+        const UNINIT: SystemRng = SystemRng::uninit();
+        mock::install(mock::Mock::frozen());
+        let mut rng = UNINIT;
+        mock::with(|mock| mock.time = mock.time.map(|time| time + 5_000_000));
+        let first = rng.next_u64();
+        assert_eq!(SystemRng::new().next_u64(), first);
+        assert_ne!(rng.next_u64(), first);
+    }
+
+    #[test]
+    fn parallel_streams_are_pairwise_distinct() {
+        mock::install(mock::Mock::frozen());
+        let mut parent = SystemRng::new();
+        let streams: Vec<[u64; 8]> = parent
+            .parallel_streams::<4>()
+            .into_iter()
+            .chain([parent.fork(), parent])
+            .map(|mut rng| core::array::from_fn(|_| rng.next_u64()))
+            .collect();
+        let values: Vec<u64> = streams.iter().flatten().copied().collect();
+        assert!((1..values.len()).all(|i| !values[..i].contains(&values[i])));
+    }
+
+    #[test]
+    fn the_hash_version_is_part_of_every_seed() {
+        let mut accumulated = crate::hasher();
+        accumulated.write(b"same input");
+        let current = crate::squeeze_256(&accumulated);
+        assert_eq!(
+            crate::squeeze_256_as(&accumulated, crate::HASH_VERSION),
+            current
+        );
+        assert_ne!(
+            crate::squeeze_256_as(&accumulated, crate::HASH_VERSION + 1),
+            current
+        );
+        assert_eq!(SystemRng::hash_version(), crate::HASH_VERSION);
+    }
+
+    #[test]
+    fn advice_names_the_jittering_ports() {
+        // SAFETY: The ports never reach the SDK; the mock backend only reads their numbers.
+        let peripherals = unsafe { vexide_devices::peripherals::Peripherals::steal() };
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let free = |jittering: &'static [u8]| {
+            mock::install(mock::Mock {
+                time_step: 1000,
+                adi: Some(Box::new(move |port| {
+                    if jittering.contains(&port.number()) {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        (state >> 52) as i32
+                    } else {
+                        2048
+                    }
+                })),
+                ..mock::Mock::frozen()
+            });
+            SystemRng::advise(&peripherals)
+        };
+
+        let advice = free(&[3, 6]).unwrap();
+        assert_eq!(advice.free_ports, 0b0010_0100);
+        assert_eq!(advice.free_port_count(), 2);
+        assert_eq!(advice.additional_bits(), 2.0 * ADI_PORT_ENTROPY_BITS);
+        assert_eq!(free(&[]), None);
+    }
+
+    #[test]
+    fn only_the_tick_separates_frozen_draws() {
+        static TICK: AtomicU64 = AtomicU64::new(0);
+        static OTHER_TICK: AtomicU64 = AtomicU64::new(0);
+        mock::install(mock::Mock::frozen());
+        let mut ticked = SystemRng::new().with_tick_source(&TICK);
+        let mut other = SystemRng::new().with_tick_source(&OTHER_TICK);
+        assert_eq!(ticked.next_u64(), other.next_u64());
+
+        TICK.fetch_add(1, Ordering::Relaxed);
+        assert_ne!(ticked.next_u64(), other.next_u64());
+        OTHER_TICK.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(ticked.next_u64(), other.next_u64());
+    }
+
+    // The deterministic readings replace the battery, so its delay never applies.
+    #[cfg(all(feature = "battery", not(feature = "deterministic")))]
+    #[test]
+    fn profiles_rank_sources_by_their_delay() {
+        // The timers follow the system clock, and every read of the battery current sleeps for a millisecond.
+        mock::install(mock::Mock {
+            powerup_time: Some(5_000_000),
+            current: Some(Box::new(|| {
+                std::thread::sleep(core::time::Duration::from_millis(1));
+                0.0
+            })),
+            ..mock::Mock::default()
+        });
+        let profile: Vec<_> = SystemRng::new().profile_sources(20).collect();
+        let sources: Vec<_> = profile.iter().map(|&(source, _)| source).collect();
+        assert_eq!(
+            sources,
+            [
+                SourceFlags::BATTERY,
+                SourceFlags::PROGRAM_TIME,
+                SourceFlags::POWERUP_TIME
+            ]
+        );
+        let (_, battery) = profile[0];
+        assert!(battery >= core::time::Duration::from_millis(1));
+        assert!(profile[1..].iter().all(|&(_, cost)| cost < battery / 10));
+    }
+
+    #[test]
+    fn the_field_state_is_mixed_only_when_asked() {
+        mock::install(mock::Mock::frozen());
+        let mut plain = SystemRng::new();
+        let mut fielded = SystemRng::new().with_field_time();
+        let mut other = SystemRng::new().with_field_time();
+        plain.next_u64();
+        assert_eq!(mock::calls().competition_status, 0);
+        assert_eq!(fielded.next_u64(), other.next_u64());
+        assert_eq!(mock::calls().competition_status, 2);
+
+        mock::with(|mock| mock.competition_status = 0b1100_0000);
+        let advanced = fielded.next_u64();
+        mock::with(|mock| mock.competition_status = 0);
+        assert_ne!(advanced, other.next_u64());
     }
 
-    fn fill_bytes(&mut self, dest: &mut [u8]) {
-        dest.chunks_mut(4)
-            .map(|chunk| {
-                let len = chunk.len();
-                let value = self.hash_value();
-                chunk.copy_from_slice(&value.to_le_bytes()[..len]);
-            })
-            .count();
+    #[test]
+    fn idle_robots_read_the_metrics_more_often() {
+        let reads_per_draw = |profile| {
+            mock::install(mock::Mock::frozen());
+            let mut rng = SystemRng::new().with_environment_profile(profile);
+            let before = mock::calls().powerup_time;
+            rng.next_u64();
+            mock::calls().powerup_time - before
+        };
+        let idle = reads_per_draw(EnvironmentProfile::Idle);
+        let active = reads_per_draw(EnvironmentProfile::Active);
+        assert!(idle > active);
+        assert_eq!(
+            idle * u64::from(EnvironmentProfile::Active.rounds()),
+            active * u64::from(EnvironmentProfile::Idle.rounds())
+        );
     }
 }
@@ -0,0 +1,99 @@
+use core::hash::Hasher;
+
+use vexide_core::io::Read;
+
+use crate::{backend::Instant, source::EntropySource};
+
+/// The size of the buffer received data is read into.
+const READ_CHUNK_LEN: usize = 64;
+
+/// The most chunks read in a single sample, so a busy link can't stall a draw.
+const MAX_CHUNKS_PER_SAMPLE: usize = 8;
+
+/// An [`EntropySource`] that mixes in data received over a radio link or serial port.
+///
+/// Any reader works, including vexide's `RadioLink` and `SerialPort`.
+/// Each sample reads whatever has arrived since the last one and mixes in both the bytes
+/// and the time since data last arrived, measured with the high-resolution timer.
+/// The other endpoint's data and the timing of its packets are unpredictable to anyone who can't observe the link,
+/// which makes this useful for cooperative randomness between robots.
+///
+/// This only contributes entropy while data is actually being received.
+/// Read errors (like a radio that hasn't linked yet) are treated as no data.
+#[derive(Debug)]
+pub struct LinkSource<R> {
+    reader: R,
+    last_arrival: Instant,
+}
+impl<R: Read> LinkSource<R> {
+    /// Create a new `LinkSource` that reads received data from `reader`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{CompositeRng, LinkSource};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(p: Peripherals) {
+    ///     let link = RadioLink::open(p.port_1, "veranda", LinkType::Worker);
+    ///     let mut rng = CompositeRng::new(LinkSource::new(link));
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            last_arrival: Instant::now(),
+        }
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> EntropySource for LinkSource<R> {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        let mut buf = [0; READ_CHUNK_LEN];
+        for _ in 0..MAX_CHUNKS_PER_SAMPLE {
+            let len = match self.reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => len,
+            };
+            let now = Instant::now();
+            hasher.write_u64(now.duration_since(self.last_arrival).as_micros() as u64);
+            hasher.write(&buf[..len]);
+            self.last_arrival = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use super::LinkSource;
+    use crate::{backend::mock, hasher, source::EntropySource};
+
+    /// Sample a link on which `packet` arrives `wait` microseconds after the source is created.
+    fn sampled(packet: &[u8], wait: u64) -> u64 {
+        mock::install(mock::Mock::frozen());
+        let mut source = LinkSource::new(packet);
+        mock::with(|mock| mock.time = mock.time.map(|time| time + wait));
+        let mut hasher = hasher();
+        source.sample(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn received_bytes_and_timing_are_mixed() {
+        assert_eq!(sampled(&[], 100), hasher().finish());
+        let packet = sampled(b"ping 1", 100);
+        assert_eq!(sampled(b"ping 1", 100), packet);
+        assert_ne!(sampled(b"ping 2", 100), packet);
+        assert_ne!(sampled(b"ping 1", 200), packet);
+    }
+}
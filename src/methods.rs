@@ -0,0 +1,801 @@
+//! Methods shared by every hardware RNG.
+
+/// Implement the configuration, seeding, and drawing methods shared by
+/// [`SystemRng`](crate::SystemRng) and [`AdiRng`](crate::AdiRng), along with [`RngCore`](rand::RngCore).
+///
+/// The RNG must have a `state` and a `buffer`, and its own `accumulate`, `status`, and `estimated_entropy_bits`.
+macro_rules! impl_hardware_rng {
+    ($rng:ty) => {
+        impl $rng {
+            /// Mix a nonce that is unique to this power cycle into every draw.
+            ///
+            /// The nonce is derived once, from the brain's powerup time and a fresh sample of the system metrics,
+            /// and is stored separately from the seed. Seed material saved to the SD card and loaded again on a later boot
+            /// (for example, with [`with_seed_bytes`](SystemRng::with_seed_bytes)) therefore never reproduces a previous boot's stream,
+            /// even if the hardware readings happen to match. The flip side is that output can't be replayed across boots
+            /// from a saved seed while this is enabled. The nonce is kept by [`reset_to_fresh`](Self::reset_to_fresh).
+            pub fn with_power_cycle_nonce(mut self) -> Self {
+                self.state.capture_boot_nonce();
+                self
+            }
+
+            /// Mix the brain's unique identity into the state of this RNG.
+            ///
+            /// This keeps two brains running the same program from producing synchronized streams.
+            /// The SDK does not currently expose a serial number or any other per-brain identity,
+            /// so for now this does nothing. It will start taking effect once an identity is available.
+            pub fn with_device_identity(mut self) -> Self {
+                self.state.mix_device_identity();
+                self
+            }
+
+            /// Mix the change in the battery readings since the last draw, instead of their absolute values.
+            ///
+            /// The battery voltage and current barely move between draws, so their absolute values contribute
+            /// almost nothing after the first one. Their changes are tiny, but that jitter is where the entropy actually is,
+            /// so mixing them at a higher resolution extracts more from a source that is always present.
+            /// This has no effect without the `battery` feature.
+            pub fn with_battery_noise_amplification(mut self) -> Self {
+                self.state.amplify_battery = true;
+                self
+            }
+
+            /// Check that this RNG can plausibly provide at least `bits` bits of entropy per draw.
+            ///
+            /// This runs a startup self-test that checks that the system metrics change between readings,
+            /// then compares the [entropy estimate](Self::estimated_entropy_bits) against `bits`.
+            /// Use this when randomness really matters, to fail loudly at startup instead of silently running with a weak configuration.
+            ///
+            /// # Errors
+            ///
+            /// - An [`InitError::SelfTestFailed`] error is returned if the system metrics never changed.
+            /// - An [`InitError::InsufficientEntropy`] error is returned if the entropy estimate is below `bits`.
+            pub fn with_min_entropy(mut self, bits: f32) -> Result<Self, InitError> {
+                if health::inputs_stuck(&mut self.state) {
+                    return Err(InitError::SelfTestFailed);
+                }
+                let estimated_bits = self.estimated_entropy_bits();
+                if estimated_bits < bits {
+                    return Err(InitError::InsufficientEntropy {
+                        estimated_bits,
+                        required_bits: bits,
+                    });
+                }
+                Ok(self)
+            }
+
+            /// Choose how the inputs of each draw are combined.
+            ///
+            /// The default is [`SequentialHash`]. Changing the strategy changes every output of the RNG,
+            /// so this is mostly useful for experimenting with alternatives like [`XorOfHashes`].
+            /// The strategy is kept by [`reset_to_fresh`](Self::reset_to_fresh).
+            pub fn with_mix_strategy<M: MixStrategy>(mut self, _strategy: M) -> Self {
+                self.state.mix = MixFn::of::<M>();
+                self
+            }
+
+            /// Record this RNG's health after every draw, for printing from a panic hook.
+            ///
+            /// The status and draw count are stored in a global after each draw made through [`RngCore`],
+            /// where [`health::print_recorded_health`] and [`health::last_recorded_health`] can read them.
+            /// This takes a lock on every draw, so it costs a little throughput.
+            #[cfg(feature = "panic-report")]
+            pub fn with_health_recording(mut self) -> Self {
+                self.state.record_health = true;
+                self
+            }
+
+            /// Resample any draw that exactly repeats the previous output.
+            ///
+            /// The draw counter already makes consecutive repeats practically impossible, so this is a belt-and-suspenders option
+            /// for callers that can't tolerate one at all. A draw is resampled up to 4 times. If it still repeats,
+            /// it is returned anyway rather than looping forever on a stuck source,
+            /// and [`status`](Self::status) reports [`EntropyWarning::RepeatedOutput`].
+            pub fn with_resample_on_equal(mut self) -> Self {
+                self.state.resample_on_equal = true;
+                self
+            }
+
+            /// Call `callback` as soon as a draw detects that the timers have stalled.
+            ///
+            /// The callback runs once, on the draw that first reports [`EntropyWarning::StuckTimers`],
+            /// and again only if the timers recover and then stall again. It runs in the middle of the draw,
+            /// so it should be cheap, like setting a flag or logging a line, and must not panic.
+            /// It can't draw from this RNG, since the RNG is borrowed while the callback runs.
+            /// The callback is kept by [`reset_to_fresh`](Self::reset_to_fresh).
+            pub fn with_alarm_on_stall(mut self, callback: fn()) -> Self {
+                self.state.on_stall = Some(StallAlarm(callback));
+                self
+            }
+
+            /// Mix a tick counter maintained by the program into every draw.
+            ///
+            /// In a fixed-rate control loop, incrementing `tick` once per iteration guarantees that
+            /// each iteration's draws see an input that has advanced, independent of the timers' resolution,
+            /// even if every sensor is frozen. The counter is only read, never written,
+            /// and it is kept by [`reset_to_fresh`](Self::reset_to_fresh).
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use core::sync::atomic::{AtomicU64, Ordering};
+            ///
+            /// use rand::RngCore;
+            /// use veranda::SystemRng;
+            /// use vexide::prelude::*;
+            ///
+            /// static TICK: AtomicU64 = AtomicU64::new(0);
+            ///
+            /// #[vexide::main]
+            /// async fn main(_: Peripherals) {
+            ///     let mut rng = SystemRng::new().with_tick_source(&TICK);
+            ///     loop {
+            ///         TICK.fetch_add(1, Ordering::Relaxed);
+            ///         println!("Random number: {}", rng.next_u64());
+            ///         sleep(core::time::Duration::from_millis(10)).await;
+            ///     }
+            /// }
+            /// ```
+            pub fn with_tick_source(mut self, tick: &'static AtomicU64) -> Self {
+                self.state.tick = Some(TickSource(tick));
+                self
+            }
+
+            /// Mix the field controller's shared match state into every draw.
+            ///
+            /// Every robot on the field sees the same value, which ties each draw to the phase of the match it was made in.
+            /// It is a public input: anyone watching the field knows it, so it adds no secrecy on its own,
+            /// and all unpredictability still comes from the stored seed (such as a per-robot secret from
+            /// [`with_seed_bytes`](SystemRng::with_seed_bytes)) and the hardware. Since each robot's own hardware readings
+            /// are still mixed in, this doesn't make two robots' output agree.
+            ///
+            /// The SDK does not currently expose the field's match timer, so for now only the competition status
+            /// (the current mode and whether the field is connected) is mixed in. The timer will be mixed in once it is available.
+            pub fn with_field_time(mut self) -> Self {
+                self.state.field_time = true;
+                self
+            }
+
+            /// Tune how each draw samples the hardware for the conditions the robot runs in.
+            ///
+            /// [`EnvironmentProfile::Idle`] reads the system metrics several times per draw and adds CPU timing jitter
+            /// to make up for quiet sensors, while [`EnvironmentProfile::Active`] reads them once for lower latency
+            /// and relies on the noise the motors put on the battery current. See [`EnvironmentProfile`] for the details.
+            /// The [entropy estimate](Self::estimated_entropy_bits) doesn't credit either profile,
+            /// since the extra inputs are hard to measure and can't be relied on.
+            pub fn with_environment_profile(mut self, profile: EnvironmentProfile) -> Self {
+                self.state.apply_profile(profile);
+                self
+            }
+
+            /// Set the starting value of the internal draw counter.
+            ///
+            /// The counter is mixed into every draw so that back-to-back draws never hash identical inputs.
+            /// Pinning its start is useful for reproducing a sequence in tests where the hardware inputs are also fixed,
+            /// but it does not make real hardware output reproducible, since the system metrics still vary.
+            pub fn with_counter_start(mut self, start: u64) -> Self {
+                self.state.counter = start;
+                self
+            }
+
+            /// Permanently mix the output of another RNG into the state of this one.
+            ///
+            /// A word is drawn from `other` and folded into the stored seed, so every future draw depends on both RNGs.
+            pub fn merge(&mut self, other: &mut impl RngCore) {
+                let value = other.next_u64();
+                self.state.mix_seed(|hasher| hasher.write_u64(value));
+            }
+
+            /// Contribute a fresh sample of every entropy source to `pool`.
+            pub fn drain_entropy_into(&mut self, pool: &mut EntropyPool) {
+                pool.add(&self.next_u64().to_le_bytes());
+            }
+
+            /// Fold 32 bytes drawn from `source` into the stored seed.
+            ///
+            /// This is a heavier version of [`merge`](Self::merge) for injecting externally gathered randomness.
+            /// In tests, seeding from a deterministic RNG pins down every part of the state that isn't read from hardware.
+            pub fn seed_from_rng(&mut self, source: &mut impl RngCore) {
+                let mut seed = [0; 32];
+                source.fill_bytes(&mut seed);
+                self.state.mix_seed(|hasher| hasher.write(&seed));
+            }
+
+            /// Stir a fresh sample of every entropy source into the stored seed.
+            ///
+            /// Unlike [`reset_to_fresh`](Self::reset_to_fresh), this keeps all existing state, so it can only add entropy.
+            pub fn reseed(&mut self) {
+                let value = self.hash_value();
+                self.state.mix_seed(|hasher| hasher.write_u64(value));
+                self.state.last_reseed = Instant::now();
+            }
+
+            /// [`reseed`](Self::reseed) this RNG if it was created or last reseeded more than `max_age` ago.
+            ///
+            /// Returns `true` if the RNG was stale and has been reseeded. Calling this before draws gives cheap,
+            /// draw-driven freshness without running a separate task.
+            pub fn reseed_if_stale(&mut self, max_age: Duration) -> bool {
+                if self.state.last_reseed.elapsed() < max_age {
+                    return false;
+                }
+                self.reseed();
+                true
+            }
+
+            /// Sample every entropy source once and squeeze a 256-bit seed out of the result.
+            ///
+            /// This is more efficient than four separate draws when seeding a 256-bit RNG,
+            /// since the hardware is only sampled once. The seed is produced by re-hashing the accumulated
+            /// state with an incrementing counter, so its four words are distinct.
+            /// Keep in mind that the seed contains no more entropy than a single draw.
+            pub fn finalize_256(&mut self) -> [u8; 32] {
+                squeeze_256(&self.accumulate())
+            }
+
+            /// Sample every entropy source once and squeeze `N` distinct 64-bit seeds out of the result.
+            ///
+            /// This is meant for seeding one small PRNG per task, like [`SmallRng`](rand::rngs::SmallRng), from a single hardware sample.
+            /// The seeds are guaranteed to be pairwise distinct, but like [`finalize_256`](Self::finalize_256),
+            /// together they contain no more entropy than a single draw.
+            pub fn seed_array<const N: usize>(&mut self) -> [u64; N] {
+                squeeze_distinct(&self.accumulate())
+            }
+
+            /// Like [`finalize_256`](Self::finalize_256), but wrap the seed in a [`Secret`] so it can't be logged by accident.
+            #[cfg(feature = "crypto")]
+            pub fn finalize_256_secret(&mut self) -> Secret<[u8; 32]> {
+                Secret::new(self.finalize_256())
+            }
+
+            /// Generate `N` bytes of key material, refusing to if the startup self-test fails.
+            ///
+            /// The self-test is run right before every key. It reads the system metrics a few times and fails
+            /// if they never change, which catches stalled timers and a collapsed backend, but not inputs that are merely weak.
+            /// On success, a 256-bit seed is [finalized](Self::finalize_256) from the hardware and expanded with ChaCha20,
+            /// and the seed is zeroized. Keep in mind that the key contains no more entropy than the seed,
+            /// so keys longer than 32 bytes aren't any stronger.
+            ///
+            /// # Errors
+            ///
+            /// - An [`InitError::SelfTestFailed`] error is returned if the system metrics never changed.
+            #[cfg(feature = "crypto")]
+            pub fn generate_key<const N: usize>(&mut self) -> Result<[u8; N], InitError> {
+                if health::inputs_stuck(&mut self.state) {
+                    return Err(InitError::SelfTestFailed);
+                }
+                Ok(crypto::expand_key(self.finalize_256()))
+            }
+
+            /// Returns the number of calls that produced output since this RNG was created or reset.
+            ///
+            /// Every method that returns random bits counts: the [`RngCore`] methods, [`next_u8`](Self::next_u8),
+            /// [`next_u16`](Self::next_u16), [`take_bytes`](Self::take_bytes), and reads through [`io::Read`].
+            /// Together with [`bytes_generated`](Self::bytes_generated), this is cheap enough to poll every loop,
+            /// for example to graph usage over a match, and both are included in [`status`](Self::status).
+            /// A call counts once no matter how many hashes it took.
+            pub const fn draw_count(&self) -> u64 {
+                self.state.draw_calls
+            }
+
+            /// Returns the number of bytes returned by the calls counted by [`draw_count`](Self::draw_count)
+            /// since this RNG was created or reset.
+            pub const fn bytes_generated(&self) -> u64 {
+                self.state.bytes_generated
+            }
+
+            /// Returns the fraction of the most recent system readings that were distinct from each other, from `0.0` to `1.0`.
+            ///
+            /// The last 32 draws' readings are kept, and the ratio is measured over them,
+            /// so this is cheap enough to check continuously as a liveness signal.
+            /// The output itself always changes because of the draw counter, but a ratio dropping toward `0.0`
+            /// means the inputs behind it are stalling. Before the first draw, the ratio is `0.0`.
+            pub fn distinctness_ratio(&self) -> f32 {
+                self.state.distinct_ratio()
+            }
+
+            /// Fill `dest` with random bytes, failing if the output is obviously not random.
+            ///
+            /// This is a last line of defense for filling key material and other crypto-adjacent buffers.
+            /// If every byte in a buffer of at least [`MIN_CHECKED_FILL_LEN`](health::MIN_CHECKED_FILL_LEN) bytes
+            /// has the same value, every entropy source has almost certainly collapsed and an error is returned.
+            /// Shorter buffers are never rejected.
+            ///
+            /// # Errors
+            ///
+            /// - A [`FillError::ConstantOutput`] error is returned if the filled buffer is a single repeated byte.
+            pub fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), FillError> {
+                self.fill_bytes(dest);
+                if health::is_collapsed(dest) {
+                    return Err(FillError::ConstantOutput);
+                }
+                Ok(())
+            }
+
+            /// Returns a random `u8`.
+            ///
+            /// Bytes are served from a buffer, so a single hash is shared between up to eight calls
+            /// instead of each call sampling the hardware again.
+            pub fn next_u8(&mut self) -> u8 {
+                u8::from_le_bytes(self.take_bytes())
+            }
+
+            /// Returns a random `u16`.
+            ///
+            /// Like [`next_u8`](Self::next_u8), this is served from a buffer,
+            /// so a single hash is shared between up to four calls.
+            pub fn next_u16(&mut self) -> u16 {
+                u16::from_le_bytes(self.take_bytes())
+            }
+
+            /// Returns `K` random bytes.
+            ///
+            /// Like [`next_u8`](Self::next_u8), this is served from a buffer, and any bytes left over from the last hash
+            /// are used before sampling the hardware again. This makes it efficient to pull several small fields
+            /// of different sizes: starting from an empty buffer, taking 3 bytes and then 5 bytes only hashes once.
+            pub fn take_bytes<const K: usize>(&mut self) -> [u8; K] {
+                let mut bytes = [0; K];
+                self.fill_buffered(&mut bytes);
+                bytes
+            }
+
+            /// Draw a value from `distribution` using this RNG.
+            ///
+            /// This is the same as [`Rng::sample`](rand::Rng::sample), but doesn't require importing [`rand::Rng`].
+            pub fn sample<T, D: Distribution<T>>(&mut self, distribution: D) -> T {
+                distribution.sample(self)
+            }
+
+            fn fill_buffered(&mut self, dest: &mut [u8]) {
+                self.state.count_output(dest.len());
+                for byte in dest {
+                    *byte = match self.buffer.pop() {
+                        Some(byte) => byte,
+                        None => {
+                            let value = self.hash_value();
+                            self.buffer.refill(value);
+                            self.buffer.pop().unwrap()
+                        }
+                    };
+                }
+            }
+
+            fn hash_value(&mut self) -> u64 {
+                let mut value = self.accumulate().finish();
+                let mut resamples = 0;
+                while resamples < MAX_RESAMPLES && self.state.is_repeat(value) {
+                    value = self.accumulate().finish();
+                    resamples += 1;
+                }
+                self.state.finish_output(value);
+                self.record_health();
+                value
+            }
+
+            /// Record this RNG's health for panic reports, if recording is enabled.
+            fn record_health(&self) {
+                #[cfg(feature = "panic-report")]
+                if self.state.record_health {
+                    health::record_health(health::RecordedHealth {
+                        status: self.status(),
+                        draws: self.state.draws,
+                    });
+                }
+            }
+        }
+
+        impl RngCore for $rng {
+            fn next_u32(&mut self) -> u32 {
+                self.state.count_output(4);
+                self.hash_value() as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                self.state.count_output(8);
+                self.hash_value()
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.state.count_output(dest.len());
+                dest.chunks_mut(4)
+                    .map(|chunk| {
+                        let len = chunk.len();
+                        let value = self.hash_value();
+                        chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+                    })
+                    .count();
+
+                #[cfg(feature = "panic-on-collapse")]
+                if health::is_collapsed(dest) {
+                    panic!(
+                        "every entropy source has collapsed: filled a buffer with a single repeated byte"
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{cell::Cell, hash::Hasher, time::Duration};
+
+    use rand::{rngs::mock::StepRng, RngCore};
+
+    use crate::{
+        backend::mock::{self, ports},
+        health::{EntropyWarning, InitError},
+        source::SourceFlags,
+        AdiRng, MixInput, MixStrategy, SequentialHash, SystemRng, XorOfHashes,
+    };
+
+    #[test]
+    fn with_min_entropy_credits_every_port() {
+        let required = AdiRng::new(&ports::<2>()).estimated_entropy_bits();
+        let one = ports::<1>();
+        let three = ports::<3>();
+        assert!(matches!(
+            AdiRng::new(&one).with_min_entropy(required),
+            Err(InitError::InsufficientEntropy { .. })
+        ));
+        assert!(AdiRng::new(&three).with_min_entropy(required).is_ok());
+    }
+
+    // The deterministic readings replace the frozen mock, so the timers never stall.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn with_min_entropy_refuses_a_stuck_backend() {
+        mock::install(mock::Mock::frozen());
+        assert_eq!(
+            SystemRng::new().with_min_entropy(0.0).err(),
+            Some(InitError::SelfTestFailed)
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn generate_key_succeeds_on_a_healthy_backend() {
+        let mut rng = SystemRng::new();
+        let first = rng.generate_key::<32>().unwrap();
+        assert_ne!(rng.generate_key::<32>().unwrap(), first);
+    }
+
+    // The deterministic readings replace the frozen mock, so the self-test never trips.
+    #[cfg(all(feature = "crypto", not(feature = "deterministic")))]
+    #[test]
+    fn generate_key_refuses_a_stuck_backend() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        assert_eq!(rng.generate_key::<32>(), Err(InitError::SelfTestFailed));
+    }
+
+    #[test]
+    fn eight_next_u8_calls_share_one_hash() {
+        let mut rng = SystemRng::new();
+        let before = mock::calls().powerup_time;
+        let bytes: [u8; 8] = core::array::from_fn(|_| rng.next_u8());
+        assert_eq!(mock::calls().powerup_time - before, 1);
+        assert!(bytes.iter().any(|&byte| byte != bytes[0]));
+    }
+
+    #[test]
+    fn different_device_identities_diverge() {
+        let first_draw = |identity| {
+            mock::install(mock::Mock {
+                identity,
+                ..mock::Mock::frozen()
+            });
+            SystemRng::new().with_device_identity().next_u64()
+        };
+        assert_ne!(first_draw(Some(1)), first_draw(Some(2)));
+        assert_ne!(first_draw(Some(1)), first_draw(None));
+        assert_eq!(first_draw(Some(1)), first_draw(Some(1)));
+    }
+
+    #[test]
+    fn counters_cover_every_kind_of_draw() {
+        let mut rng = SystemRng::new();
+        let start = (rng.draw_count(), rng.bytes_generated());
+
+        rng.next_u32();
+        rng.next_u64();
+        rng.fill_bytes(&mut [0; 5]);
+        rng.next_u8();
+        rng.next_u16();
+        rng.take_bytes::<3>();
+        vexide_core::io::Read::read(&mut rng, &mut [0; 7]).unwrap();
+
+        let status = rng.status();
+        assert_eq!(
+            (
+                status.draw_count - start.0,
+                status.bytes_generated - start.1
+            ),
+            (7, 4 + 8 + 5 + 1 + 2 + 3 + 7)
+        );
+        assert_eq!(
+            (status.draw_count, status.bytes_generated),
+            (rng.draw_count(), rng.bytes_generated())
+        );
+    }
+
+    #[test]
+    fn the_same_counter_start_reproduces_the_sequence() {
+        mock::install(mock::Mock::frozen());
+        let sequence = |start| {
+            let mut rng = SystemRng::new().with_counter_start(start);
+            core::array::from_fn::<u64, 8, _>(|_| rng.next_u64())
+        };
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+    }
+
+    #[test]
+    fn merging_changes_the_stream() {
+        mock::install(mock::Mock::frozen());
+        let draws = |rng: &mut SystemRng| core::array::from_fn::<u64, 4, _>(|_| rng.next_u64());
+        let plain = draws(&mut SystemRng::new());
+
+        let mut merged = SystemRng::new();
+        merged.merge(&mut StepRng::new(1, 1));
+        let merged = draws(&mut merged);
+        assert!(merged.iter().zip(&plain).all(|(a, b)| a != b));
+
+        let mut other = SystemRng::new();
+        other.merge(&mut StepRng::new(2, 1));
+        assert_ne!(draws(&mut other), merged);
+    }
+
+    #[test]
+    fn finalize_256_words_are_distinct() {
+        let mut rng = SystemRng::new();
+        let seed = rng.finalize_256();
+        let words: [u64; 4] =
+            core::array::from_fn(|i| u64::from_le_bytes(seed[i * 8..][..8].try_into().unwrap()));
+        for (i, word) in words.iter().enumerate() {
+            assert!(words[i + 1..].iter().all(|other| other != word));
+        }
+        assert_ne!(rng.finalize_256(), seed);
+    }
+
+    // The deterministic readings replace the mocked battery.
+    #[cfg(all(feature = "battery", not(feature = "deterministic")))]
+    #[test]
+    fn amplified_battery_jitter_changes_the_stream() {
+        let draws = |amplify: bool, jitter: [f64; 4]| {
+            mock::install(mock::Mock::frozen());
+            let mut rng = SystemRng::new();
+            if amplify {
+                rng = rng.with_battery_noise_amplification();
+            }
+            jitter.map(|jitter| {
+                mock::with(|mock| mock.battery = (12.0 + jitter, 1.0));
+                rng.next_u64()
+            })
+        };
+        // The jitter is below the thousandths that absolute readings are mixed in, so only the changes see it.
+        let first = [0.0, 0.0002, 0.0004, 0.0001];
+        let second = [0.0, 0.0003, 0.0001, 0.0004];
+        assert_eq!(draws(false, first), draws(false, second));
+        let (first, second) = (draws(true, first), draws(true, second));
+        assert_eq!(first[0], second[0]);
+        assert!((1..4).all(|draw| first[draw] != second[draw]));
+    }
+
+    #[test]
+    fn small_byte_arrays_share_one_hash() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let hashes = mock::calls().powerup_time;
+        let first: [u8; 3] = rng.take_bytes();
+        let second: [u8; 5] = rng.take_bytes();
+        assert_eq!(mock::calls().powerup_time, hashes + 1);
+        assert_ne!(first[..], second[..3]);
+        let _: [u8; 1] = rng.take_bytes();
+        assert_eq!(mock::calls().powerup_time, hashes + 2);
+    }
+
+    #[test]
+    fn seed_arrays_are_distinct_within_and_across_calls() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new();
+        let hashes = mock::calls().powerup_time;
+        let first: [u64; 16] = rng.seed_array();
+        let second: [u64; 16] = rng.seed_array();
+        assert_eq!(mock::calls().powerup_time, hashes + 2);
+        let seeds: [u64; 32] = core::array::from_fn(|i| [first, second][i / 16][i % 16]);
+        assert!((1..seeds.len()).all(|i| !seeds[..i].contains(&seeds[i])));
+    }
+
+    #[test]
+    fn reseed_if_stale_waits_for_the_age_threshold() {
+        mock::install(mock::Mock::frozen());
+        let advance = |micros| mock::with(|mock| mock.time = mock.time.map(|time| time + micros));
+        let max_age = Duration::from_millis(500);
+        let mut rng = SystemRng::new();
+        advance(499_999);
+        assert!(!rng.reseed_if_stale(max_age));
+        advance(1);
+        assert!(rng.reseed_if_stale(max_age));
+        assert!(!rng.reseed_if_stale(max_age));
+        advance(500_000);
+        assert!(rng.reseed_if_stale(max_age));
+    }
+
+    #[test]
+    fn a_wrapped_counter_does_not_repeat_the_sequence() {
+        mock::install(mock::Mock::frozen());
+        let mut fresh = SystemRng::new();
+        let start: [u64; 4] = core::array::from_fn(|_| fresh.next_u64());
+
+        let mut wrapping = SystemRng::new().with_counter_start(u64::MAX);
+        wrapping.next_u64();
+        let wrapped: [u64; 4] = core::array::from_fn(|_| wrapping.next_u64());
+        assert!(wrapped.iter().all(|value| !start.contains(value)));
+    }
+
+    #[test]
+    fn mix_strategies_select_the_combination() {
+        mock::install(mock::Mock::frozen());
+        let draws = |mut rng: SystemRng| core::array::from_fn::<u64, 4, _>(|_| rng.next_u64());
+        let default = draws(SystemRng::new());
+        assert_eq!(
+            draws(SystemRng::new().with_mix_strategy(SequentialHash)),
+            default
+        );
+        let xor = draws(SystemRng::new().with_mix_strategy(XorOfHashes));
+        assert!(xor.iter().all(|value| !default.contains(value)));
+
+        /// A strategy that only keeps the first input, the seed, discarding the readings and counter.
+        struct SeedOnly;
+
+        impl MixStrategy for SeedOnly {
+            fn mix(hasher: &mut dyn Hasher, inputs: &[MixInput<'_>]) {
+                inputs[0](hasher);
+            }
+        }
+
+        let seed_only = draws(SystemRng::new().with_mix_strategy(SeedOnly));
+        assert!(seed_only.iter().all(|&value| value == seed_only[0]));
+    }
+
+    // The deterministic readings replace the frozen mock, so the readings never repeat.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn distinctness_tracks_whether_the_readings_change() {
+        mock::install(mock::Mock {
+            time_step: 1,
+            ..mock::Mock::frozen()
+        });
+        let mut varying = SystemRng::new();
+        assert_eq!(varying.distinctness_ratio(), 0.0);
+        for _ in 0..64 {
+            varying.next_u64();
+        }
+        assert_eq!(varying.distinctness_ratio(), 1.0);
+
+        mock::install(mock::Mock::frozen());
+        let mut stuck = SystemRng::new();
+        for _ in 0..64 {
+            stuck.next_u64();
+        }
+        assert!(stuck.distinctness_ratio() < 0.1);
+    }
+
+    #[test]
+    fn seeding_from_a_fixed_rng_is_reproducible() {
+        mock::install(mock::Mock::frozen());
+        let stream = |start| {
+            let mut rng = SystemRng::new();
+            rng.seed_from_rng(&mut StepRng::new(start, 1));
+            core::array::from_fn::<u64, 4, _>(|_| rng.next_u64())
+        };
+        assert_eq!(stream(7), stream(7));
+        assert_ne!(stream(7), stream(8));
+        assert_ne!(
+            stream(7),
+            core::array::from_fn(|_| SystemRng::new().next_u64())
+        );
+    }
+
+    #[test]
+    fn the_power_cycle_nonce_separates_boots() {
+        let first_draw = |powerup_time, nonce: bool| {
+            mock::install(mock::Mock {
+                powerup_time: Some(powerup_time),
+                ..mock::Mock::frozen()
+            });
+            let mut rng = SystemRng::with_seed_bytes(b"saved seed");
+            // Leave the powerup time out of the draws, so that only the nonce can see it.
+            rng.disable_source(SourceFlags::POWERUP_TIME);
+            if nonce {
+                rng = rng.with_power_cycle_nonce();
+            }
+            rng.next_u64()
+        };
+        assert_eq!(first_draw(5_000_000, false), first_draw(7_000_000, false));
+        assert_ne!(first_draw(5_000_000, true), first_draw(7_000_000, true));
+        assert_eq!(first_draw(5_000_000, true), first_draw(5_000_000, true));
+    }
+
+    std::thread_local! {
+        /// How many more draws [`Constant`] mixes to the same value, on this thread.
+        static CONSTANT_MIXES: Cell<u32> = const { Cell::new(0) };
+    }
+
+    /// A strategy that ignores every input while [`CONSTANT_MIXES`] lasts, then mixes them in order.
+    struct Constant;
+
+    impl MixStrategy for Constant {
+        fn mix(hasher: &mut dyn Hasher, inputs: &[MixInput<'_>]) {
+            let remaining = CONSTANT_MIXES.get();
+            if remaining > 0 {
+                CONSTANT_MIXES.set(remaining - 1);
+                return;
+            }
+            SequentialHash::mix(hasher, inputs);
+        }
+    }
+
+    #[test]
+    fn a_repeated_draw_is_resampled() {
+        let first_two = |resample: bool| {
+            CONSTANT_MIXES.set(2);
+            let mut rng = SystemRng::new().with_mix_strategy(Constant);
+            if resample {
+                rng = rng.with_resample_on_equal();
+            }
+            let draws = [rng.next_u64(), rng.next_u64()];
+            (draws, rng.status().warning)
+        };
+        let ([first, second], _) = first_two(false);
+        assert_eq!(first, second);
+        let ([first, second], warning) = first_two(true);
+        assert_ne!(first, second);
+        assert_eq!(warning, None);
+
+        CONSTANT_MIXES.set(u32::MAX);
+        let mut stuck = SystemRng::new()
+            .with_mix_strategy(Constant)
+            .with_resample_on_equal();
+        assert_eq!(stuck.next_u64(), stuck.next_u64());
+        assert_eq!(stuck.status().warning, Some(EntropyWarning::RepeatedOutput));
+    }
+
+    // The deterministic readings replace the frozen mock, so the timers never stall.
+    #[cfg(not(feature = "deterministic"))]
+    #[test]
+    fn the_stall_alarm_fires_once_per_stall() {
+        use crate::state::STUCK_AFTER_DRAWS;
+
+        std::thread_local! {
+            /// How many times [`count_alarm`] has been called on this thread.
+            static ALARMS: Cell<u32> = const { Cell::new(0) };
+        }
+
+        fn count_alarm() {
+            ALARMS.set(ALARMS.get() + 1);
+        }
+
+        mock::install(mock::Mock::frozen());
+        let mut rng = SystemRng::new().with_alarm_on_stall(count_alarm);
+        let mut draws = 0;
+        while ALARMS.get() == 0 {
+            assert_eq!(rng.status().warning, None);
+            rng.next_u64();
+            draws += 1;
+        }
+        assert_eq!(draws, STUCK_AFTER_DRAWS + 1);
+        assert_eq!(rng.status().warning, Some(EntropyWarning::StuckTimers));
+        for _ in 0..2000 {
+            rng.next_u64();
+        }
+        assert_eq!(ALARMS.get(), 1);
+
+        // Once the timers recover, a second stall raises the alarm again.
+        mock::with(|mock| mock.time = mock.time.map(|time| time + 1));
+        for _ in 0..=STUCK_AFTER_DRAWS {
+            rng.next_u64();
+        }
+        assert_eq!(ALARMS.get(), 2);
+    }
+}
@@ -0,0 +1,233 @@
+use core::hash::Hasher;
+
+use crate::{hasher, squeeze_256};
+
+/// Mix `inputs` into a single word using the same hasher that finalizes every RNG draw.
+///
+/// This is a pure function: the same inputs always produce the same output on a given build,
+/// but the output is not guaranteed to stay the same between versions of veranda.
+/// It is not a cryptographic hash.
+///
+/// # Examples
+///
+/// ```
+/// let match_number = 12;
+/// let robot_id = 2;
+/// let decision = veranda::mix64(&[match_number, robot_id]);
+/// let go_left = decision & 1 == 0;
+/// ```
+pub fn mix64(inputs: &[u64]) -> u64 {
+    let mut hasher = hasher();
+    for &input in inputs {
+        hasher.write_u64(input);
+    }
+    hasher.finish()
+}
+
+/// Derive an independent 256-bit sub-seed from `seed` for the purpose named by `label`.
+///
+/// This follows the shape of HKDF-Expand: the seed and label are hashed together and the result is expanded
+/// with an incrementing counter, so `derive_subseed(&seed, b"enc")` and `derive_subseed(&seed, b"mac")`
+/// are unrelated to each other and to `seed`. It is a pure function, so the same inputs always give the same output
+/// on a given build. Like the rest of veranda's mixing, it is not built on a cryptographic hash.
+///
+/// # Examples
+///
+/// ```
+/// use veranda::{derive_subseed, SystemRng};
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(_: Peripherals) {
+///     let seed = SystemRng::new().finalize_256();
+///     let auton_seed = derive_subseed(&seed, b"autonomous");
+///     let driver_seed = derive_subseed(&seed, b"driver");
+/// }
+/// ```
+pub fn derive_subseed(seed: &[u8; 32], label: &[u8]) -> [u8; 32] {
+    let mut hasher = hasher();
+    hasher.write(seed);
+    hasher.write_usize(label.len());
+    hasher.write(label);
+    squeeze_256(&hasher)
+}
+
+/// Combine several 256-bit seeds into a single seed.
+///
+/// This is a building block for cooperative randomness: each robot produces a seed with `finalize_256`,
+/// the seeds are exchanged, and every robot combines them into the same agreed seed.
+/// The result is unpredictable as long as any one of the input seeds is.
+///
+/// The seeds are hashed in order, so the result depends on the order of `seeds`
+/// and every participant must pass them in the same order (for example, sorted by robot ID).
+/// The result is deterministic: the same seeds in the same order always produce the same output.
+///
+/// # Examples
+///
+/// ```
+/// use veranda::{combine_seeds, SystemRng};
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(_: Peripherals) {
+///     let ours = SystemRng::new().finalize_256();
+///     let theirs = [0; 32]; // Received from the other robot.
+///     let shared = combine_seeds(&[ours, theirs]);
+///     println!("{shared:?}");
+/// }
+/// ```
+pub fn combine_seeds(seeds: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = hasher();
+    hasher.write_usize(seeds.len());
+    for seed in seeds {
+        hasher.write(seed);
+    }
+    squeeze_256(&hasher)
+}
+
+/// A single input to a [`MixStrategy`], which writes one source's contribution into a hasher.
+pub type MixInput<'a> = &'a dyn Fn(&mut dyn Hasher);
+
+/// A policy for combining every input of a draw into the hasher that finalizes it.
+///
+/// Each draw of a hardware RNG has several inputs: the stored seed, each system metric, and the draw counter.
+/// A strategy decides how they end up in the final hasher. The default, [`SequentialHash`],
+/// feeds them into it one after another. Custom strategies are mostly useful for experimentation,
+/// and are selected with `with_mix_strategy` on [`SystemRng`](crate::SystemRng) or [`AdiRng`](crate::AdiRng).
+pub trait MixStrategy {
+    /// Combine `inputs` into `hasher`.
+    fn mix(hasher: &mut dyn Hasher, inputs: &[MixInput<'_>]);
+}
+
+/// Write every input into the hasher in order. This is the default strategy.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SequentialHash;
+
+impl MixStrategy for SequentialHash {
+    fn mix(hasher: &mut dyn Hasher, inputs: &[MixInput<'_>]) {
+        for input in inputs {
+            input(hasher);
+        }
+    }
+}
+
+/// Hash every input separately and write the XOR of the hashes into the hasher.
+///
+/// Each input is hashed together with its position, so identical inputs in different positions don't cancel out.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct XorOfHashes;
+
+impl MixStrategy for XorOfHashes {
+    fn mix(hasher: &mut dyn Hasher, inputs: &[MixInput<'_>]) {
+        let mut combined = 0;
+        for (index, input) in inputs.iter().enumerate() {
+            let mut input_hasher = crate::hasher();
+            input_hasher.write_usize(index);
+            input(&mut input_hasher);
+            combined ^= input_hasher.finish();
+        }
+        hasher.write_u64(combined);
+    }
+}
+
+/// The [`MixStrategy::mix`] of a strategy, stored by an RNG to remember which one it uses.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MixFn(pub(crate) fn(&mut dyn Hasher, &[MixInput<'_>]));
+
+impl MixFn {
+    pub(crate) const fn of<M: MixStrategy>() -> Self {
+        Self(M::mix)
+    }
+}
+
+/// Strategies are compared by address, which is good enough for telling configurations apart,
+/// since the same strategy is only ever stored through [`MixFn::of`].
+impl PartialEq for MixFn {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for MixFn {}
+
+/// Write a floating-point sensor reading into `hasher`, in thousandths of a unit.
+///
+/// Every float source goes through this function so that the conversion is handled the same way everywhere:
+/// `NaN` is written as zero, and infinite or out-of-range readings saturate instead of wrapping.
+pub(crate) fn write_float(hasher: &mut (impl Hasher + ?Sized), value: f64) {
+    let scaled = if value.is_nan() {
+        0
+    } else {
+        (value * 1000.0).clamp(f64::from(i32::MIN), f64::from(i32::MAX)) as i32
+    };
+    hasher.write_i32(scaled);
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher;
+
+    use super::{combine_seeds, derive_subseed, mix64, write_float};
+
+    /// A hasher that keeps the last `i32` written to it.
+    #[derive(Default)]
+    struct LastI32(Option<i32>);
+
+    impl Hasher for LastI32 {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {
+            unreachable!("floats are only written as i32s");
+        }
+
+        fn write_i32(&mut self, value: i32) {
+            self.0 = Some(value);
+        }
+    }
+
+    fn written(value: f64) -> i32 {
+        let mut hasher = LastI32::default();
+        write_float(&mut hasher, value);
+        hasher.0.unwrap()
+    }
+
+    #[test]
+    fn float_readings_saturate() {
+        assert_eq!(written(f64::NAN), 0);
+        assert_eq!(written(12.6035), 12603);
+        assert_eq!(written(-1.5), -1500);
+        assert_eq!(written(1e12), i32::MAX);
+        assert_eq!(written(-1e12), i32::MIN);
+        assert_eq!(written(f64::INFINITY), i32::MAX);
+        assert_eq!(written(f64::NEG_INFINITY), i32::MIN);
+    }
+
+    #[test]
+    fn mix64_is_pure_and_order_sensitive() {
+        assert_eq!(mix64(&[12, 2]), mix64(&[12, 2]));
+        assert_ne!(mix64(&[12, 2]), mix64(&[2, 12]));
+        assert_ne!(mix64(&[12]), mix64(&[12, 0]));
+    }
+
+    #[test]
+    fn combined_seeds_are_deterministic_and_ordered() {
+        let (a, b) = ([1; 32], [2; 32]);
+        assert_eq!(combine_seeds(&[a, b]), combine_seeds(&[a, b]));
+        assert_ne!(combine_seeds(&[a, b]), combine_seeds(&[b, a]));
+        assert_ne!(combine_seeds(&[a, b]), combine_seeds(&[a, [3; 32]]));
+        assert_ne!(combine_seeds(&[a]), a);
+    }
+
+    #[test]
+    fn subseeds_are_deterministic_and_separated_by_label() {
+        let seed = [7; 32];
+        let auton = derive_subseed(&seed, b"autonomous");
+        assert_eq!(derive_subseed(&seed, b"autonomous"), auton);
+        assert_ne!(derive_subseed(&seed, b"driver"), auton);
+        assert_ne!(derive_subseed(&[8; 32], b"autonomous"), auton);
+        // The label's length is hashed too, so an empty label differs from a zero byte.
+        assert_ne!(derive_subseed(&seed, b""), derive_subseed(&seed, &[0]));
+    }
+}
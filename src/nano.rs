@@ -0,0 +1,33 @@
+use rand::RngCore;
+
+use crate::SystemRng;
+
+/// Every draw samples the system metrics, just like [`RngCore::next_u64`].
+impl nanorand::Rng<8> for SystemRng {
+    fn rand(&mut self) -> [u8; 8] {
+        self.next_u64().to_le_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::{Rng, WyRand};
+
+    use crate::{SystemRng, VerandaRngExt};
+
+    #[test]
+    fn a_seeded_nanorand_generator_varies() {
+        let mut wyrand = WyRand::new_seed(0);
+        let unseeded = WyRand::new_seed(0).generate::<u64>();
+        SystemRng::new().seed_nanorand(&mut wyrand);
+        let values: [u64; 4] = core::array::from_fn(|_| wyrand.generate());
+        assert_ne!(values[0], unseeded);
+        assert!((1..4).all(|i| !values[..i].contains(&values[i])));
+    }
+
+    #[test]
+    fn system_rng_draws_through_nanorand() {
+        let mut rng = SystemRng::new();
+        assert_ne!(rng.generate::<u64>(), rng.generate::<u64>());
+    }
+}
@@ -0,0 +1,63 @@
+use core::{hash::Hasher, time::Duration};
+
+use ahash::AHasher;
+use vexide_devices::adi::AdiPort;
+
+use crate::{
+    adi,
+    backend::{self, Instant},
+};
+
+/// How long a noisy device's reading can stay unchanged before it is considered constant.
+pub(crate) const CONSTANT_AFTER: Duration = Duration::from_millis(500);
+
+/// A connected analog device whose readings jitter enough to be used as entropy.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct NoisyDevice<'a> {
+    port: &'a AdiPort,
+    /// A running average of the readings, in sixteenths, used to remove any DC bias.
+    average: i32,
+    last_value: i32,
+    last_change: Instant,
+}
+
+impl<'a> NoisyDevice<'a> {
+    pub(crate) fn new(port: &'a AdiPort) -> Self {
+        let value = backend::adi_value(port);
+        Self {
+            port,
+            average: value << 4,
+            last_value: value,
+            last_change: Instant::now(),
+        }
+    }
+
+    /// Read the device and write its deviation from the running average into `hasher`.
+    pub(crate) fn sample(&mut self, hasher: &mut AHasher) {
+        let value = backend::adi_value(self.port);
+        if value != self.last_value {
+            self.last_value = value;
+            self.last_change = Instant::now();
+        }
+
+        let scaled = value << 4;
+        self.average += (scaled - self.average) >> 3;
+        adi::write_port_id(hasher, self.port);
+        hasher.write_i32(scaled - self.average);
+    }
+
+    /// Returns the running average, which carries over between draws.
+    pub(crate) fn average(&self) -> i32 {
+        self.average
+    }
+
+    /// Replace the running average, for restoring a snapshot.
+    pub(crate) fn set_average(&mut self, average: i32) {
+        self.average = average;
+    }
+
+    /// Returns `true` if the device's reading hasn't changed recently enough to be contributing entropy.
+    pub(crate) fn is_constant(&self) -> bool {
+        self.last_change.elapsed() > CONSTANT_AFTER
+    }
+}
@@ -0,0 +1,107 @@
+use core::hash::Hasher;
+
+use ahash::AHasher;
+
+use crate::{hasher, squeeze_256};
+
+/// A long-lived pool that entropy from many sources is stirred into, and that seeds are occasionally extracted from.
+///
+/// Unlike an [`EntropyAccumulator`](crate::EntropyAccumulator), which gathers from a single RNG and is consumed
+/// to produce one seed, a pool lives for the whole program: any number of RNGs and sources can
+/// [`add`](Self::add) to it whenever they have something, and [`extract`](Self::extract) can be called repeatedly.
+/// Every extraction re-keys the pool, so a seed can't be recomputed from the pool's later state.
+/// Like the rest of veranda's mixing, this is not built on a cryptographic hash.
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+///
+/// use veranda::{EntropyPool, SystemRng};
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(_: Peripherals) {
+///     let mut rng = SystemRng::new();
+///     let mut pool = EntropyPool::new();
+///     for _ in 0..32 {
+///         rng.drain_entropy_into(&mut pool);
+///         sleep(Duration::from_millis(10)).await;
+///     }
+///     let seed = pool.extract();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EntropyPool {
+    hasher: AHasher,
+}
+impl EntropyPool {
+    /// Create a new, empty `EntropyPool`.
+    pub fn new() -> Self {
+        Self { hasher: hasher() }
+    }
+
+    /// Fold `bytes` into the pool.
+    pub fn add(&mut self, bytes: &[u8]) {
+        self.hasher.write_usize(bytes.len());
+        self.hasher.write(bytes);
+    }
+
+    /// Squeeze a 256-bit seed out of everything added so far, then re-key the pool.
+    ///
+    /// The pool keeps the entropy it has gathered, so extracting twice in a row gives two different seeds,
+    /// but neither seed is any stronger than the entropy that was added before it.
+    pub fn extract(&mut self) -> [u8; 32] {
+        let seed = squeeze_256(&self.hasher);
+
+        // Replace the state with a one-way function of itself that is separate from the seed's words.
+        let mut rekey = self.hasher.clone();
+        rekey.write_u64(u64::MAX);
+        self.hasher = hasher();
+        self.hasher.write_u64(rekey.finish());
+
+        seed
+    }
+}
+
+impl Default for EntropyPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntropyPool;
+    use crate::SystemRng;
+
+    #[test]
+    fn extracted_seeds_follow_the_inputs() {
+        let seed = |inputs: &[&[u8]]| {
+            let mut pool = EntropyPool::new();
+            for input in inputs {
+                pool.add(input);
+            }
+            pool.extract()
+        };
+        let varied = seed(&[b"battery", b"timer"]);
+        assert_eq!(seed(&[b"battery", b"timer"]), varied);
+        assert_ne!(seed(&[b"battery", b"clock"]), varied);
+        assert_ne!(seed(&[b"batter", b"ytimer"]), varied);
+        assert_ne!(seed(&[]), varied);
+        assert!(varied.iter().any(|&byte| byte != varied[0]));
+    }
+
+    #[test]
+    fn extraction_rekeys_the_pool() {
+        let mut pool = EntropyPool::new();
+        let mut rng = SystemRng::new();
+        for _ in 0..8 {
+            rng.drain_entropy_into(&mut pool);
+        }
+        let mut copy = pool.clone();
+        let first = pool.extract();
+        assert_eq!(copy.extract(), first);
+        assert_ne!(pool.extract(), first);
+    }
+}
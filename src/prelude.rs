@@ -0,0 +1,42 @@
+//! Commonly used traits and types, re-exported for a single glob import.
+//!
+//! This also re-exports [`Rng`] and [`RngCore`] from `rand`, so drawing numbers doesn't need a separate import.
+//!
+//! # Examples
+//!
+//! ```
+//! use veranda::prelude::*;
+//! use vexide::prelude::*;
+//!
+//! #[vexide::main]
+//! async fn main(_: Peripherals) {
+//!     let mut rng = SystemRng::new();
+//!     println!("Random number: {}", rng.next_u64());
+//!     println!("Random angle: {}", rng.random_range(0.0..360.0));
+//! }
+//! ```
+
+pub use rand::{Rng, RngCore};
+
+#[cfg(feature = "crypto")]
+pub use crate::CryptoVeranda;
+pub use crate::{
+    source::EntropySource, AdiEdgeSource, AdiRng, AnyRng, CombinedRng, CompositeRng,
+    CurrentJitterSource, EntropyAccumulator, EntropyPool, EnvironmentProfile, FastRng, JitterRng,
+    LinkSource, RandomBits, RateLimited, SharedRng, SystemRng, U32Rng, VerandaRngExt, Whitened,
+    XorRng,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_prelude_is_enough_to_draw() {
+        let mut rng = SystemRng::new();
+        let angle: f64 = rng.random_range(0.0..360.0);
+        assert!((0.0..360.0).contains(&angle));
+        assert_ne!(rng.next_u64(), rng.next_u64());
+        assert_ne!(rng.next_nonzero_u64().get(), 0);
+    }
+}
@@ -0,0 +1,28 @@
+/// The conditions a hardware RNG is expected to run in, for tuning how it samples with a single setting.
+///
+/// The system metrics behave very differently between a robot idling on a bench and one in the middle of a match.
+/// Set a profile with `with_environment_profile` on [`SystemRng`](crate::SystemRng) or [`AdiRng`](crate::AdiRng).
+/// Without one, each draw reads the system metrics once and nothing else is changed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EnvironmentProfile {
+    /// The robot is sitting still, so the battery readings and other sensors barely move.
+    ///
+    /// Each draw reads the system metrics several times and also mixes in the timing jitter of a small CPU workload,
+    /// the same measurement [`JitterRng`](crate::JitterRng) is built on. Draws are noticeably slower.
+    Idle,
+    /// The robot is driving, so the motors keep the battery current noisy.
+    ///
+    /// Each draw reads the system metrics once, for the lowest latency, and mixes the change in the battery readings,
+    /// as with `with_battery_noise_amplification`, to make the most of the motor noise.
+    Active,
+}
+
+impl EnvironmentProfile {
+    /// Returns how many times each draw reads the system metrics under this profile.
+    pub const fn rounds(self) -> u32 {
+        match self {
+            Self::Idle => 4,
+            Self::Active => 1,
+        }
+    }
+}
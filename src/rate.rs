@@ -0,0 +1,148 @@
+use core::{hash::Hasher, time::Duration};
+
+use rand::RngCore;
+
+use crate::{backend::Instant, hasher};
+
+/// An RNG adapter that samples an inner hardware RNG at most once per interval.
+///
+/// Between samples, output is squeezed out of the last sample in counter mode, so it keeps advancing
+/// without touching the hardware. This protects the device telemetry APIs from being hammered in a hot loop,
+/// at the cost of every output within an interval sharing the entropy of a single sample.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RateLimited<R> {
+    inner: R,
+    min_interval: Duration,
+    last_sample: u64,
+    sampled_at: Option<Instant>,
+    counter: u64,
+}
+impl<R: RngCore> RateLimited<R> {
+    /// Create a new `RateLimited` RNG that draws from `inner` at most once every `min_interval`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::time::Duration;
+    ///
+    /// use rand::RngCore;
+    /// use veranda::{RateLimited, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = RateLimited::new(SystemRng::new(), Duration::from_millis(5));
+    ///     loop {
+    ///         println!("Random number: {}", rng.next_u64());
+    ///     }
+    /// }
+    /// ```
+    pub const fn new(inner: R, min_interval: Duration) -> Self {
+        Self {
+            inner,
+            min_interval,
+            last_sample: 0,
+            sampled_at: None,
+            counter: 0,
+        }
+    }
+
+    /// Returns the inner RNG.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Fill `dest` with random bytes, returning how many of them came from a fresh hardware sample.
+    ///
+    /// Only the first word squeezed out of each sample counts as fresh; the rest are deterministic expansion
+    /// of an earlier sample. This is useful for crypto-adjacent callers deciding whether a fill is trustworthy.
+    /// Output is the same as [`fill_bytes`](RngCore::fill_bytes).
+    pub fn checked_fill(&mut self, dest: &mut [u8]) -> usize {
+        let mut fresh = 0;
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let (value, sampled) = self.draw();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+            if sampled {
+                fresh += len;
+            }
+        }
+        fresh
+    }
+
+    /// Draw a word, returning whether the hardware was sampled for it.
+    fn draw(&mut self) -> (u64, bool) {
+        let due = self
+            .sampled_at
+            .is_none_or(|sampled_at| sampled_at.elapsed() >= self.min_interval);
+        if due {
+            self.last_sample = self.inner.next_u64();
+            self.sampled_at = Some(Instant::now());
+            self.counter = 0;
+        }
+
+        let mut hasher = hasher();
+        hasher.write_u64(self.last_sample);
+        hasher.write_u64(self.counter);
+        self.counter = self.counter.wrapping_add(1);
+        (hasher.finish(), due)
+    }
+}
+
+impl<R: RngCore> RngCore for RateLimited<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.draw().0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use rand::{rngs::mock::StepRng, RngCore};
+
+    use super::RateLimited;
+    use crate::backend::mock;
+
+    #[test]
+    fn the_inner_rng_is_sampled_once_per_interval() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = RateLimited::new(StepRng::new(0, 1), Duration::from_millis(10));
+        let mut outputs = [0; 100];
+        for (ms, output) in (0..).zip(&mut outputs) {
+            mock::with(|mock| mock.time = Some(1_000_000 + ms * 1000));
+            *output = rng.next_u64();
+        }
+
+        // Over 100ms, the step RNG is drawn at 0ms, 10ms, ... 90ms, so its next value is 10.
+        assert_eq!(rng.into_inner().next_u64(), 10);
+        assert!((1..100).all(|i| !outputs[..i].contains(&outputs[i])));
+    }
+
+    #[test]
+    fn checked_fills_count_only_the_sampled_word() {
+        mock::install(mock::Mock::frozen());
+        let mut rng = RateLimited::new(StepRng::new(0, 1), Duration::from_millis(10));
+        let mut first = [0; 32];
+        assert_eq!(rng.checked_fill(&mut first), 8);
+        let mut second = [0; 32];
+        assert_eq!(rng.checked_fill(&mut second), 0);
+        assert_ne!(first, second);
+
+        mock::with(|mock| mock.time = mock.time.map(|time| time + 10_000));
+        let mut short = [0; 5];
+        assert_eq!(rng.checked_fill(&mut short), 5);
+    }
+}
@@ -0,0 +1,165 @@
+use core::hash::Hasher;
+
+use rand::RngCore;
+
+use crate::{hasher, state::State, SystemRng};
+
+/// The raw system metrics read for a single draw of a [`SystemRng`].
+///
+/// Samples can be captured on the robot with [`SystemRng::with_recorder`] and fed back into a [`ReplayRng`]
+/// to reproduce the exact same output later, for example when debugging a match on a computer.
+/// Inputs that were disabled with [`SystemRng::disable_source`] are `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawSample {
+    /// The battery voltage and current, in volts and amps.
+    pub battery: Option<(f64, f64)>,
+    /// The time since the RNG was created, in microseconds.
+    pub program_time: Option<u128>,
+    /// The time since the brain was powered on, in microseconds.
+    pub powerup_time: Option<u64>,
+}
+
+/// A [`SystemRng`] that passes every [`RawSample`] it reads to a sink as it draws.
+///
+/// This is created with [`SystemRng::with_recorder`]. Recording has no cost for RNGs that aren't wrapped in it.
+#[derive(Debug, Clone)]
+pub struct Recording<F> {
+    rng: SystemRng,
+    sink: F,
+}
+impl<F: FnMut(RawSample)> Recording<F> {
+    pub(crate) const fn new(rng: SystemRng, sink: F) -> Self {
+        Self { rng, sink }
+    }
+
+    /// Stop recording and return the inner RNG.
+    pub fn into_inner(self) -> SystemRng {
+        self.rng
+    }
+}
+
+impl<F: FnMut(RawSample)> RngCore for Recording<F> {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.recorded_hash_value(&mut self.sink)
+    }
+
+    // This must consume samples exactly like `SystemRng::fill_bytes` so that the stream can be replayed.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+/// An RNG that reproduces the output of a [`SystemRng`] from previously recorded [`RawSample`]s.
+///
+/// The original RNG must have been created with [`SystemRng::new`] and not seeded any further
+/// (for example with [`SystemRng::merge`] or [`SystemRng::with_counter_start`]), since that state isn't recorded.
+/// Battery noise amplification isn't supported either.
+/// Each draw consumes one sample, exactly like the original RNG read one.
+#[derive(Debug, Clone)]
+pub struct ReplayRng<I> {
+    samples: I,
+    state: State,
+}
+impl<I: Iterator<Item = RawSample>> ReplayRng<I> {
+    /// Create a new `ReplayRng` that replays `samples` in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{ReplayRng, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut samples = [None; 4];
+    ///     let mut index = 0;
+    ///     let mut rng = SystemRng::new().with_recorder(|sample| {
+    ///         samples[index] = Some(sample);
+    ///         index += 1;
+    ///     });
+    ///     let original = [rng.next_u64(), rng.next_u64(), rng.next_u64(), rng.next_u64()];
+    ///
+    ///     let mut replay = ReplayRng::new(samples.into_iter().flatten());
+    ///     for value in original {
+    ///         assert_eq!(replay.next_u64(), value);
+    ///     }
+    /// }
+    /// ```
+    pub fn new(samples: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            samples: samples.into_iter(),
+            state: State::new(),
+        }
+    }
+
+    /// Returns the samples that haven't been replayed yet.
+    pub fn into_inner(self) -> I {
+        self.samples
+    }
+}
+
+impl<I: Iterator<Item = RawSample>> RngCore for ReplayRng<I> {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    /// # Panics
+    ///
+    /// Panics if every recorded sample has already been replayed.
+    fn next_u64(&mut self) -> u64 {
+        let sample = self
+            .samples
+            .next()
+            .expect("ReplayRng ran out of recorded samples");
+        let mut hasher = hasher();
+        self.state.write_sample(&mut hasher, &sample);
+        hasher.finish()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use std::vec::Vec;
+
+    use super::ReplayRng;
+    use crate::SystemRng;
+
+    #[test]
+    fn recorded_samples_replay_the_same_stream() {
+        let mut samples = Vec::new();
+        let mut rng = SystemRng::new().with_recorder(|sample| samples.push(sample));
+        let original = [rng.next_u64(), rng.next_u64(), rng.next_u64()];
+        let mut bytes = [0; 10];
+        rng.fill_bytes(&mut bytes);
+        assert_eq!(samples.len(), 6);
+
+        let mut replay = ReplayRng::new(samples);
+        assert_eq!(
+            [replay.next_u64(), replay.next_u64(), replay.next_u64()],
+            original
+        );
+        let mut replayed = [0; 10];
+        replay.fill_bytes(&mut replayed);
+        assert_eq!(replayed, bytes);
+        assert_eq!(replay.into_inner().count(), 0);
+    }
+}
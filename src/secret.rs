@@ -0,0 +1,62 @@
+use core::fmt;
+
+use zeroize::Zeroize;
+
+/// A value that should be kept out of logs, like a seed for crypto use.
+///
+/// `Secret` redacts its contents from [`Debug`](fmt::Debug) and [`Display`](fmt::Display) output,
+/// so it can't end up on the brain's console by accident, and zeroes them when it is dropped.
+/// The value can only be read by explicitly calling [`expose`](Self::expose).
+#[derive(Clone, Eq, PartialEq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    /// Wrap `value` in a `Secret`.
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns a reference to the secret value.
+    pub const fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+
+    use super::Secret;
+
+    #[test]
+    fn formatting_is_redacted_but_expose_returns_the_value() {
+        let secret = Secret::new([0xab_u8; 4]);
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(secret.expose(), &[0xab; 4]);
+    }
+}
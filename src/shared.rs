@@ -0,0 +1,243 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt;
+
+use rand::RngCore;
+use vexide_core::sync::Mutex;
+
+use crate::SystemRng;
+
+/// The RNG returned by [`global`].
+static GLOBAL: SharedRng<GlobalRng> =
+    SharedRng::new(GlobalRng(GlobalInner::System(SystemRng::uninit())));
+
+/// Returns a [`SharedRng`] that the whole program can draw from, without passing an RNG around.
+///
+/// It starts out as a [`SystemRng::new`], so it only uses the system metrics and no ADI ports,
+/// and every call returns the same instance. The RNG is created without reading anything,
+/// and its creation instant is anchored on the first draw.
+///
+/// To use a better source, replace it once at startup, before anything draws from it.
+/// A differently configured `SystemRng` can be swapped in directly, and with the `alloc` feature,
+/// [`reconfigure_global`] installs any RNG:
+///
+/// ```
+/// use rand::RngCore;
+/// use veranda::SystemRng;
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(_: Peripherals) {
+///     veranda::global().with(|rng| *rng = SystemRng::max_entropy().into());
+///     println!("Random number: {}", veranda::global().next_u64());
+/// }
+/// ```
+pub fn global() -> &'static SharedRng<GlobalRng> {
+    &GLOBAL
+}
+
+/// Replace the RNG behind [`global`] with `rng`, so that every later draw from `global()` uses it.
+///
+/// Call this once, early in `main`, before any task draws from the global RNG.
+/// Calling it after the global RNG is already in use races with the other tasks using it:
+/// draws made before the swap still come from the old RNG.
+///
+/// # Panics
+///
+/// Panics if called from inside a call to [`with`](SharedRng::with) on the global RNG.
+///
+/// # Examples
+///
+/// ```
+/// extern crate alloc;
+///
+/// use alloc::boxed::Box;
+///
+/// use rand::RngCore;
+/// use veranda::CombinedRng;
+/// use vexide::{devices::adi::AdiPort, prelude::*};
+///
+/// #[vexide::main]
+/// async fn main(p: Peripherals) {
+///     let ports: &'static [AdiPort] = Box::leak(Box::new([p.adi_g, p.adi_h]));
+///     if let Ok(rng) = CombinedRng::standard(ports) {
+///         veranda::reconfigure_global(rng);
+///     }
+///     println!("Random number: {}", veranda::global().next_u64());
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+pub fn reconfigure_global(rng: impl RngCore + Send + 'static) {
+    GLOBAL.with(|global| *global = GlobalRng(GlobalInner::Custom(Box::new(rng))));
+}
+
+/// The RNG behind [`global`].
+///
+/// This is a [`SystemRng`] unless it has been replaced with [`reconfigure_global`].
+pub struct GlobalRng(GlobalInner);
+
+// The global RNG lives in a static, so there is no point boxing the `SystemRng` to shrink it.
+#[allow(clippy::large_enum_variant)]
+enum GlobalInner {
+    System(SystemRng),
+    #[cfg(feature = "alloc")]
+    Custom(Box<dyn RngCore + Send>),
+}
+
+impl fmt::Debug for GlobalRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            GlobalInner::System(rng) => f.debug_tuple("GlobalRng").field(rng).finish(),
+            #[cfg(feature = "alloc")]
+            GlobalInner::Custom(_) => f.debug_tuple("GlobalRng").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl From<SystemRng> for GlobalRng {
+    fn from(rng: SystemRng) -> Self {
+        Self(GlobalInner::System(rng))
+    }
+}
+
+impl RngCore for GlobalRng {
+    fn next_u32(&mut self) -> u32 {
+        match &mut self.0 {
+            GlobalInner::System(rng) => rng.next_u32(),
+            #[cfg(feature = "alloc")]
+            GlobalInner::Custom(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match &mut self.0 {
+            GlobalInner::System(rng) => rng.next_u64(),
+            #[cfg(feature = "alloc")]
+            GlobalInner::Custom(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match &mut self.0 {
+            GlobalInner::System(rng) => rng.fill_bytes(dest),
+            #[cfg(feature = "alloc")]
+            GlobalInner::Custom(rng) => rng.fill_bytes(dest),
+        }
+    }
+}
+
+/// An RNG that can be shared between tasks.
+///
+/// The inner RNG is protected by a mutex, and every access locks it for the duration of a single call.
+/// Since a lock is never held across an `.await`, a `SharedRng` can't deadlock or make tasks wait on each other.
+///
+/// `&SharedRng` implements [`RngCore`], so a shared reference can be passed anywhere an RNG is expected.
+/// This takes the lock on every call, so when drawing many values at once prefer [`with`](Self::with)
+/// to only take the lock once.
+#[derive(Debug)]
+pub struct SharedRng<R = SystemRng> {
+    rng: Mutex<R>,
+}
+impl<R> SharedRng<R> {
+    /// Create a new `SharedRng` wrapping `rng`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{SharedRng, SystemRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let rng = SharedRng::new(SystemRng::new());
+    ///     println!("Random number: {}", (&rng).next_u64());
+    /// }
+    /// ```
+    pub const fn new(rng: R) -> Self {
+        Self {
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Lock the inner RNG and call `f` with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from inside another call to `with` on the same `SharedRng`.
+    pub fn with<T>(&self, f: impl FnOnce(&mut R) -> T) -> T {
+        let mut rng = self
+            .rng
+            .try_lock()
+            .expect("SharedRng was accessed while already in use");
+        f(&mut rng)
+    }
+
+    /// Returns the inner RNG.
+    pub fn into_inner(self) -> R {
+        self.rng.into_inner()
+    }
+}
+
+impl<R: RngCore> RngCore for &SharedRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.with(RngCore::next_u32)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.with(RngCore::next_u64)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with(|rng| rng.fill_bytes(dest));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    #[cfg(feature = "alloc")]
+    use rand::rngs::mock::StepRng;
+    use rand::RngCore;
+
+    #[cfg(feature = "alloc")]
+    use super::reconfigure_global;
+    use super::{global, SharedRng};
+    use crate::SystemRng;
+
+    /// Held by every test that uses the global RNG, since a draw from another thread would make `with` panic.
+    static GLOBAL_TESTS: Mutex<()> = Mutex::new(());
+
+    fn draw(mut rng: impl RngCore) -> [u64; 4] {
+        core::array::from_fn(|_| rng.next_u64())
+    }
+
+    #[test]
+    fn a_shared_reference_is_an_rng() {
+        let rng = SharedRng::new(SystemRng::new());
+        let first = draw(&rng);
+        assert!((1..4).all(|i| !first[..i].contains(&first[i])));
+        assert_ne!(draw(&rng), first);
+    }
+
+    #[test]
+    fn the_global_rng_is_one_instance() {
+        let _guard = GLOBAL_TESTS.lock().unwrap();
+        assert!(core::ptr::eq(global(), global()));
+        let first = draw(global());
+        assert!((1..4).all(|i| !first[..i].contains(&first[i])));
+        assert_ne!(draw(global()), first);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn reconfigured_draws_route_through_the_new_rng() {
+        let _guard = GLOBAL_TESTS.lock().unwrap();
+        reconfigure_global(StepRng::new(7, 1));
+        let drawn = draw(global());
+        global().with(|rng| *rng = SystemRng::new().into());
+        assert_eq!(drawn, [7, 8, 9, 10]);
+        assert_ne!(draw(global()), [11, 12, 13, 14]);
+    }
+}
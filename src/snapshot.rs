@@ -0,0 +1,28 @@
+use core::fmt;
+
+use crate::{buffer::ByteBuffer, trickle::Trickle};
+
+/// A copy of the deterministic part of a hardware RNG's state, for resuming a sequence later.
+///
+/// A snapshot holds the stored seed, the draw counter, the unused bytes of the last draw, and any other state
+/// that carries over between draws, like the trickle position and a noisy device's running average. Hardware readings can't be captured, so restoring a snapshot only reproduces
+/// the same output when the readings are also the same, for example with the `deterministic` feature.
+///
+/// Snapshots are taken with `snapshot` and restored with `restore` on [`SystemRng`](crate::SystemRng)
+/// and [`AdiRng`](crate::AdiRng).
+/// A snapshot is enough to predict the RNG's output under known readings, so its [`Debug`](fmt::Debug) output is redacted.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct RngSnapshot {
+    pub(crate) seed: u64,
+    pub(crate) counter: u64,
+    pub(crate) last_battery: Option<(u64, u64)>,
+    pub(crate) buffer: ByteBuffer,
+    pub(crate) trickle: Option<Trickle>,
+    pub(crate) noisy_average: Option<i32>,
+}
+
+impl fmt::Debug for RngSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RngSnapshot([REDACTED])")
+    }
+}
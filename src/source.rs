@@ -0,0 +1,190 @@
+//! Pluggable sources of entropy.
+//!
+//! An [`EntropySource`] is anything that can be sampled for unpredictable data.
+//! Sources can be combined into tuples or arrays and turned into an RNG with [`CompositeRng`](crate::CompositeRng).
+
+use core::{fmt, hash::Hasher, ops};
+
+/// A set of the system inputs mixed into each draw of a [`SystemRng`](crate::SystemRng).
+///
+/// Flags can be combined with `|`. Every source is enabled by default.
+/// The stored seed and draw counter are always mixed in, so output keeps advancing even with every source disabled.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SourceFlags(u8);
+
+impl SourceFlags {
+    /// No system inputs.
+    pub const NONE: Self = Self(0);
+    /// The battery voltage and current. This has no effect without the `battery` feature.
+    pub const BATTERY: Self = Self(1 << 0);
+    /// The time since the RNG was created, from the high-resolution program timer.
+    pub const PROGRAM_TIME: Self = Self(1 << 1);
+    /// The time since the brain was powered on.
+    pub const POWERUP_TIME: Self = Self(1 << 2);
+    /// Every system input.
+    pub const ALL: Self = Self(Self::BATTERY.0 | Self::PROGRAM_TIME.0 | Self::POWERUP_TIME.0);
+
+    /// Returns `true` if every source in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if no sources are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Default for SourceFlags {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Lists the enabled sources separated by commas, or `none` if there are none.
+impl fmt::Display for SourceFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        let names = [
+            (Self::BATTERY, "battery"),
+            (Self::PROGRAM_TIME, "program time"),
+            (Self::POWERUP_TIME, "powerup time"),
+        ];
+        let mut first = true;
+        for (flag, name) in names {
+            if self.contains(flag) {
+                if !first {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{name}")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ops::BitOr for SourceFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for SourceFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl ops::Sub for SourceFlags {
+    type Output = Self;
+
+    /// Returns the sources in `self` that are not in `rhs`.
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
+
+/// A source of entropy that can be mixed into an RNG.
+pub trait EntropySource {
+    /// Sample this source, writing its contribution into `hasher`.
+    fn sample(&mut self, hasher: &mut dyn Hasher);
+}
+
+/// An [`EntropySource`] backed by a closure, for hardware that veranda doesn't know about.
+///
+/// The closure is called on every sample and writes whatever it reads into the hasher.
+///
+/// # Examples
+///
+/// ```
+/// use core::hash::Hasher;
+///
+/// use rand::RngCore;
+/// use veranda::{source::ClosureSource, CompositeRng};
+/// use vexide::prelude::*;
+///
+/// #[vexide::main]
+/// async fn main(p: Peripherals) {
+///     let sensor = AdiLineTracker::new(p.adi_a);
+///     let source = ClosureSource(|hasher: &mut dyn Hasher| {
+///         hasher.write_u32(sensor.raw_reflectivity().unwrap_or_default() as u32);
+///     });
+///     let mut rng = CompositeRng::new(source);
+///     println!("Random number: {}", rng.next_u64());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ClosureSource<F>(pub F);
+
+impl<F: FnMut(&mut dyn Hasher)> EntropySource for ClosureSource<F> {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        (self.0)(hasher);
+    }
+}
+
+impl<S: EntropySource + ?Sized> EntropySource for &mut S {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        (**self).sample(hasher);
+    }
+}
+
+impl<S: EntropySource, const N: usize> EntropySource for [S; N] {
+    fn sample(&mut self, hasher: &mut dyn Hasher) {
+        for source in self {
+            source.sample(hasher);
+        }
+    }
+}
+
+macro_rules! impl_tuple_source {
+    ($($name:ident),+) => {
+        impl<$($name: EntropySource),+> EntropySource for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn sample(&mut self, hasher: &mut dyn Hasher) {
+                let ($($name,)+) = self;
+                $($name.sample(hasher);)+
+            }
+        }
+    };
+}
+
+impl_tuple_source!(A);
+impl_tuple_source!(A, B);
+impl_tuple_source!(A, B, C);
+impl_tuple_source!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use core::{cell::Cell, hash::Hasher};
+
+    use rand::RngCore;
+
+    use super::ClosureSource;
+    use crate::{backend::mock, CompositeRng};
+
+    #[test]
+    fn closure_writes_influence_the_output() {
+        mock::install(mock::Mock::frozen());
+        let first_draw = |value: u64| {
+            CompositeRng::new(ClosureSource(move |hasher: &mut dyn Hasher| {
+                hasher.write_u64(value)
+            }))
+            .next_u64()
+        };
+        assert_eq!(first_draw(1), first_draw(1));
+        assert_ne!(first_draw(1), first_draw(2));
+
+        let samples = Cell::new(0);
+        let mut rng = CompositeRng::new(ClosureSource(|_: &mut dyn Hasher| {
+            samples.set(samples.get() + 1)
+        }));
+        rng.next_u64();
+        rng.next_u64();
+        assert_eq!(samples.get(), 2);
+    }
+}
@@ -0,0 +1,482 @@
+use core::{
+    fmt,
+    hash::Hasher,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ahash::AHasher;
+
+use crate::{
+    backend::{self, Instant},
+    hasher, jitter,
+    mix::{self, MixFn, SequentialHash},
+    profile::EnvironmentProfile,
+    replay::RawSample,
+    source::SourceFlags,
+};
+
+/// How many consecutive draws can read the same timer values before the timers are considered stuck.
+///
+/// Several draws can legitimately land in the same microsecond, so this is set well above that.
+pub(crate) const STUCK_AFTER_DRAWS: u32 = 1024;
+
+/// The share of [`SYSTEM_ENTROPY_BITS`](crate::health::SYSTEM_ENTROPY_BITS) contributed by each timer.
+const TIMER_ENTROPY_BITS: f32 = 2.0;
+
+/// The share of [`SYSTEM_ENTROPY_BITS`](crate::health::SYSTEM_ENTROPY_BITS) contributed by the battery readings.
+const BATTERY_ENTROPY_BITS: f32 = 4.0;
+
+/// The extra entropy credited to the battery readings when their changes are mixed instead of their absolute values.
+const AMPLIFIED_BATTERY_ENTROPY_BITS: f32 = 2.0;
+
+/// How many times a draw that repeats the previous output is resampled before it is returned anyway.
+pub(crate) const MAX_RESAMPLES: u32 = 4;
+
+/// A callback run when an RNG's timers are first detected as stuck.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StallAlarm(pub(crate) fn());
+
+/// Alarms are compared by address, which is good enough for telling configurations apart.
+impl PartialEq for StallAlarm {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for StallAlarm {}
+
+/// A tick counter maintained by the program, mixed into every draw.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TickSource(pub(crate) &'static AtomicU64);
+
+/// Tick sources are the same if they are the same counter.
+impl PartialEq for TickSource {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for TickSource {}
+
+/// The number of recent readings that [`State::distinct_ratio`] is measured over.
+const DISTINCT_WINDOW: usize = 32;
+
+/// A ring of fingerprints of the most recent system readings.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct RecentReadings {
+    fingerprints: [u64; DISTINCT_WINDOW],
+    len: usize,
+    next: usize,
+}
+
+impl RecentReadings {
+    const fn new() -> Self {
+        Self {
+            fingerprints: [0; DISTINCT_WINDOW],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    fn push(&mut self, fingerprint: u64) {
+        self.fingerprints[self.next] = fingerprint;
+        self.next = (self.next + 1) % DISTINCT_WINDOW;
+        self.len = (self.len + 1).min(DISTINCT_WINDOW);
+    }
+
+    fn distinct_ratio(&self) -> f32 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        let readings = &self.fingerprints[..self.len];
+        let distinct = (0..readings.len())
+            .filter(|&index| !readings[..index].contains(&readings[index]))
+            .count();
+        distinct as f32 / self.len as f32
+    }
+}
+
+/// The system metrics and internal state shared by every hardware RNG.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub(crate) struct State {
+    pub(crate) time_of_creation: Instant,
+    /// Extra seed material mixed into every draw.
+    pub(crate) seed: u64,
+    /// Incremented on every draw so that two draws can never hash identical inputs.
+    /// When it wraps, the seed is changed so that the sequence of inputs can never repeat.
+    pub(crate) counter: u64,
+    /// When fresh entropy was last stirred into the seed, or when the RNG was created if it never was.
+    pub(crate) last_reseed: Instant,
+    /// Which system inputs are mixed into each draw.
+    pub(crate) sources: SourceFlags,
+    /// Whether to mix the change in the battery readings instead of their absolute values.
+    pub(crate) amplify_battery: bool,
+    /// A value unique to this power cycle, mixed into every draw alongside the seed.
+    pub(crate) boot_nonce: Option<u64>,
+    /// Whether every draw records the RNG's health for panic reports.
+    #[cfg(feature = "panic-report")]
+    pub(crate) record_health: bool,
+    /// Called on the draw that first detects stuck timers.
+    pub(crate) on_stall: Option<StallAlarm>,
+    /// Whether the field's shared state is read on every draw.
+    pub(crate) field_time: bool,
+    /// A control loop tick counter read on every draw.
+    pub(crate) tick: Option<TickSource>,
+    /// Whether a draw that repeats the previous output is resampled.
+    pub(crate) resample_on_equal: bool,
+    /// How many times the system metrics are read and mixed into each draw.
+    pub(crate) rounds: u32,
+    /// Whether the timing jitter of a small CPU workload is mixed into each draw.
+    pub(crate) cpu_jitter: bool,
+    /// The previous output, kept when resampling repeated outputs.
+    last_output: Option<u64>,
+    /// Whether the last draw still repeated the previous output after every resample.
+    pub(crate) repeated_output: bool,
+    /// How the inputs of each draw are combined, from a [`MixStrategy`](crate::MixStrategy).
+    pub(crate) mix: MixFn,
+    /// The bits of the last battery voltage and current, used to compute their change.
+    pub(crate) last_battery: Option<(u64, u64)>,
+    /// The timer readings of the last draw, used to detect stuck timers.
+    last_timers: (u128, u64),
+    /// The number of consecutive draws that read the same timer values.
+    stale_draws: u32,
+    /// The number of draws made since the RNG was created or reset.
+    pub(crate) draws: u64,
+    /// The number of calls that produced output since the RNG was created or reset.
+    pub(crate) draw_calls: u64,
+    /// The number of bytes returned by those calls since the RNG was created or reset.
+    pub(crate) bytes_generated: u64,
+    /// Fingerprints of the latest readings, used to estimate how often they change.
+    recent: RecentReadings,
+    /// Whether the creation instant has been anchored yet.
+    initialized: bool,
+}
+
+/// Only the configuration and draw count are shown. The seed, nonce, and counter would let anyone
+/// reading a log predict every later draw, and the previous output and battery readings would leak earlier ones.
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State")
+            .field("sources", &self.sources)
+            .field("rounds", &self.rounds)
+            .field("draws", &self.draws)
+            .finish_non_exhaustive()
+    }
+}
+
+impl State {
+    pub(crate) fn new() -> Self {
+        let mut state = Self::uninit();
+        state.ensure_init();
+        state
+    }
+
+    /// Create a state whose creation instant is anchored lazily, on the first draw.
+    pub(crate) const fn uninit() -> Self {
+        Self {
+            time_of_creation: Instant::ZERO,
+            seed: 0,
+            counter: 0,
+            last_reseed: Instant::ZERO,
+            sources: SourceFlags::ALL,
+            amplify_battery: false,
+            boot_nonce: None,
+            on_stall: None,
+            tick: None,
+            field_time: false,
+            resample_on_equal: false,
+            rounds: 1,
+            cpu_jitter: false,
+            last_output: None,
+            repeated_output: false,
+            #[cfg(feature = "panic-report")]
+            record_health: false,
+            mix: MixFn::of::<SequentialHash>(),
+            last_battery: None,
+            last_timers: (0, 0),
+            stale_draws: 0,
+            draws: 0,
+            draw_calls: 0,
+            bytes_generated: 0,
+            recent: RecentReadings::new(),
+            initialized: false,
+        }
+    }
+
+    /// Anchor the creation instant to now, if it hasn't been already.
+    pub(crate) fn ensure_init(&mut self) {
+        if !self.initialized {
+            let now = Instant::now();
+            self.time_of_creation = now;
+            self.last_reseed = now;
+            self.initialized = true;
+        }
+    }
+
+    /// Discard everything but the configuration, as if the RNG had just been created.
+    pub(crate) fn reset(&mut self) {
+        *self = Self {
+            sources: self.sources,
+            amplify_battery: self.amplify_battery,
+            boot_nonce: self.boot_nonce,
+            on_stall: self.on_stall,
+            tick: self.tick,
+            field_time: self.field_time,
+            resample_on_equal: self.resample_on_equal,
+            rounds: self.rounds,
+            cpu_jitter: self.cpu_jitter,
+            #[cfg(feature = "panic-report")]
+            record_health: self.record_health,
+            mix: self.mix,
+            ..Self::new()
+        };
+    }
+
+    /// Returns `true` if the timers haven't advanced in a suspiciously long run of draws.
+    ///
+    /// When this happens, the counter is the only input still guaranteed to change,
+    /// so output keeps advancing deterministically instead of locking up.
+    pub(crate) fn timers_stuck(&self) -> bool {
+        self.stale_draws >= STUCK_AFTER_DRAWS
+    }
+
+    /// Returns the fraction of the latest system readings that were distinct from each other,
+    /// from `0.0` to `1.0`, or `0.0` before the first draw.
+    pub(crate) fn distinct_ratio(&self) -> f32 {
+        self.recent.distinct_ratio()
+    }
+
+    /// Returns `true` if `value` should be resampled because it repeats the previous output.
+    pub(crate) fn is_repeat(&self, value: u64) -> bool {
+        self.resample_on_equal && self.last_output == Some(value)
+    }
+
+    /// Remember `value` as the latest output, flagging it if it is still a repeat after resampling.
+    pub(crate) fn finish_output(&mut self, value: u64) {
+        if self.resample_on_equal {
+            self.repeated_output = self.last_output == Some(value);
+            self.last_output = Some(value);
+        }
+    }
+
+    /// Count a call that returned `bytes` bytes of output.
+    pub(crate) fn count_output(&mut self, bytes: usize) {
+        self.draw_calls = self.draw_calls.wrapping_add(1);
+        self.bytes_generated = self.bytes_generated.wrapping_add(bytes as u64);
+    }
+
+    /// Fold new material into the stored seed.
+    pub(crate) fn mix_seed(&mut self, mix: impl FnOnce(&mut AHasher)) {
+        let mut hasher = hasher();
+        hasher.write_u64(self.seed);
+        mix(&mut hasher);
+        self.seed = hasher.finish();
+    }
+
+    /// Fold the brain's unique identity into the stored seed, if one is available.
+    pub(crate) fn mix_device_identity(&mut self) {
+        if let Some(identity) = backend::device_identity() {
+            self.mix_seed(|hasher| hasher.write_u64(identity));
+        }
+    }
+
+    /// Derive a nonce for this power cycle from the brain's powerup time and a fresh sample of the system metrics.
+    pub(crate) fn capture_boot_nonce(&mut self) {
+        let powerup_time = backend::powerup_time();
+        let sample = self.read_sample();
+        let mut nonce = hasher();
+        nonce.write_u64(powerup_time);
+        let (voltage, current) = sample.battery.unwrap_or_default();
+        nonce.write_u64(voltage.to_bits());
+        nonce.write_u64(current.to_bits());
+        nonce.write_u128(sample.program_time.unwrap_or_default());
+        self.boot_nonce = Some(nonce.finish());
+    }
+
+    /// Returns a rough estimate of the entropy, in bits, contributed per draw by the enabled system inputs.
+    pub(crate) fn system_entropy_bits(&self) -> f32 {
+        let mut bits = 0.0;
+        if cfg!(feature = "battery") && self.sources.contains(SourceFlags::BATTERY) {
+            bits += BATTERY_ENTROPY_BITS;
+            if self.amplify_battery {
+                bits += AMPLIFIED_BATTERY_ENTROPY_BITS;
+            }
+        }
+        if self.sources.contains(SourceFlags::PROGRAM_TIME) {
+            bits += TIMER_ENTROPY_BITS;
+        }
+        if self.sources.contains(SourceFlags::POWERUP_TIME) {
+            bits += TIMER_ENTROPY_BITS;
+        }
+        bits
+    }
+
+    /// Write the system metrics and internal state into `hasher` once per round, advancing the counter each time.
+    pub(crate) fn write_inputs(&mut self, hasher: &mut AHasher) {
+        for _ in 0..self.rounds {
+            let sample = self.read_sample();
+            self.write_sample(hasher, &sample);
+        }
+        // The jitter would make deterministic readings irreproducible, so it is left out of them.
+        if self.cpu_jitter && !cfg!(feature = "deterministic") {
+            hasher.write_u64(jitter::measure_workload());
+        }
+    }
+
+    /// Apply the round count and inputs suited to `profile`.
+    pub(crate) fn apply_profile(&mut self, profile: EnvironmentProfile) {
+        self.rounds = profile.rounds();
+        self.cpu_jitter = profile == EnvironmentProfile::Idle;
+        if profile == EnvironmentProfile::Active {
+            self.amplify_battery = true;
+        }
+    }
+
+    /// Read a single system input, without any of the bookkeeping of a draw, for timing how long it takes.
+    ///
+    /// `source` must be a single source. The battery reads nothing without the `battery` feature.
+    pub(crate) fn read_source(&self, source: SourceFlags) -> u128 {
+        if source == SourceFlags::PROGRAM_TIME {
+            self.time_of_creation.elapsed().as_micros()
+        } else if source == SourceFlags::POWERUP_TIME {
+            u128::from(backend::powerup_time())
+        } else {
+            #[cfg(feature = "battery")]
+            let reading = u128::from(backend::battery_voltage().to_bits())
+                ^ u128::from(backend::battery_current().to_bits());
+            #[cfg(not(feature = "battery"))]
+            let reading = 0;
+            reading
+        }
+    }
+
+    /// Read every enabled system metric, tracking whether the timers have stopped advancing.
+    pub(crate) fn read_sample(&mut self) -> RawSample {
+        self.ensure_init();
+        #[cfg(feature = "battery")]
+        let battery = self
+            .sources
+            .contains(SourceFlags::BATTERY)
+            .then(|| (backend::battery_voltage(), backend::battery_current()));
+        #[cfg(not(feature = "battery"))]
+        let battery = None;
+        let program_time = self
+            .sources
+            .contains(SourceFlags::PROGRAM_TIME)
+            .then(|| self.time_of_creation.elapsed().as_micros());
+        let powerup_time = self
+            .sources
+            .contains(SourceFlags::POWERUP_TIME)
+            .then(backend::powerup_time);
+
+        #[cfg(feature = "deterministic")]
+        let (battery, program_time, powerup_time) =
+            crate::deterministic::readings(self.counter, battery, program_time, powerup_time);
+
+        // With both timers disabled there is nothing to check, so they are never reported as stuck.
+        let timers = (program_time.unwrap_or(0), powerup_time.unwrap_or(0));
+        if program_time.is_none() && powerup_time.is_none() {
+            self.stale_draws = 0;
+        } else if timers == self.last_timers {
+            self.stale_draws = self.stale_draws.saturating_add(1);
+            if self.stale_draws == STUCK_AFTER_DRAWS {
+                if let Some(alarm) = self.on_stall {
+                    alarm.0();
+                }
+            }
+        } else {
+            self.last_timers = timers;
+            self.stale_draws = 0;
+        }
+
+        let sample = RawSample {
+            battery,
+            program_time,
+            powerup_time,
+        };
+        let mut fingerprint = hasher();
+        let (voltage, current) = sample.battery.unwrap_or_default();
+        fingerprint.write_u64(voltage.to_bits());
+        fingerprint.write_u64(current.to_bits());
+        fingerprint.write_u128(sample.program_time.unwrap_or_default());
+        fingerprint.write_u64(sample.powerup_time.unwrap_or_default());
+        self.recent.push(fingerprint.finish());
+        sample
+    }
+
+    /// Write a sample of the system metrics and the internal state into `hasher`, advancing the counter.
+    pub(crate) fn write_sample(&mut self, hasher: &mut AHasher, sample: &RawSample) {
+        let battery = sample.battery.map(|(voltage, current)| {
+            if self.amplify_battery {
+                let (last_voltage, last_current) = self
+                    .last_battery
+                    .map_or((voltage, current), |(voltage, current)| {
+                        (f64::from_bits(voltage), f64::from_bits(current))
+                    });
+                self.last_battery = Some((voltage.to_bits(), current.to_bits()));
+                // The changes are written in millionths to keep the low-bit jitter that carries the entropy.
+                (
+                    (voltage - last_voltage) * 1000.0,
+                    (current - last_current) * 1000.0,
+                )
+            } else {
+                (voltage, current)
+            }
+        });
+        let (seed, boot_nonce, counter) = (self.seed, self.boot_nonce, self.counter);
+        let tick = self.tick.map(|tick| tick.0.load(Ordering::Relaxed));
+        let field = self.field_time.then(backend::competition_status);
+        (self.mix.0)(
+            hasher,
+            &[
+                &|hasher| {
+                    hasher.write_u64(seed);
+                    if let Some(nonce) = boot_nonce {
+                        hasher.write_u64(nonce);
+                    }
+                },
+                &|hasher| {
+                    if let Some((voltage, current)) = battery {
+                        mix::write_float(hasher, voltage);
+                        mix::write_float(hasher, current);
+                    }
+                },
+                &|hasher| {
+                    if let Some(time) = sample.program_time {
+                        hasher.write_u128(time);
+                    }
+                },
+                &|hasher| {
+                    if let Some(time) = sample.powerup_time {
+                        hasher.write_u64(time);
+                    }
+                },
+                &|hasher| {
+                    hasher.write_u64(counter);
+                    if let Some(tick) = tick {
+                        hasher.write_u64(tick);
+                    }
+                    if let Some(field) = field {
+                        hasher.write_u32(field);
+                    }
+                },
+            ],
+        );
+
+        self.draws = self.draws.wrapping_add(1);
+        let (counter, wrapped) = self.counter.overflowing_add(1);
+        self.counter = counter;
+        if wrapped {
+            // The counter has gone through a full cycle, so fold this draw's readings into the seed
+            // to keep the sequence from repeating, rather than relying on the readings happening to differ.
+            let sample = *sample;
+            self.mix_seed(|hasher| {
+                let (voltage, current) = sample.battery.unwrap_or_default();
+                hasher.write_u64(voltage.to_bits());
+                hasher.write_u64(current.to_bits());
+                hasher.write_u128(sample.program_time.unwrap_or_default());
+                hasher.write_u64(sample.powerup_time.unwrap_or_default());
+            });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use core::fmt;
+
+/// The running state of an RNG that samples one of its sources per draw.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub(crate) struct Trickle {
+    /// The hash of every previous draw, so entropy accumulates across calls.
+    pub(crate) chain: u64,
+    next_source: usize,
+}
+
+impl Trickle {
+    pub(crate) const fn new() -> Self {
+        Self {
+            chain: 0,
+            next_source: 0,
+        }
+    }
+
+    /// Returns the index of the source to sample on this draw, cycling through `count` sources.
+    ///
+    /// `count` must not be zero.
+    pub(crate) fn advance(&mut self, count: usize) -> usize {
+        let index = self.next_source % count;
+        self.next_source = (index + 1) % count;
+        index
+    }
+}
+
+/// The chained hash feeds every later draw, so only the position in the cycle is shown.
+impl fmt::Debug for Trickle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trickle")
+            .field("next_source", &self.next_source)
+            .finish_non_exhaustive()
+    }
+}
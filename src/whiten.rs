@@ -0,0 +1,113 @@
+use core::hash::Hasher;
+
+use rand::RngCore;
+
+use crate::{hasher, HASH_VERSION};
+
+/// An RNG adapter that hashes several draws from an inner RNG into each output.
+///
+/// Hashing `k` raw values together smooths out any bias or correlation between consecutive draws of a quiet source,
+/// at the cost of drawing `k` times as often from the inner RNG.
+///
+/// Every output hashes [`HASH_VERSION`] and `k` ahead of the inner draws, so the output for a given inner sequence
+/// only changes when the hash version does. Seeds derived through a `Whitened` RNG can be checked against
+/// the version they were made with.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Whitened<R> {
+    inner: R,
+    k: usize,
+}
+impl<R: RngCore> Whitened<R> {
+    /// Create a new `Whitened` RNG that draws `k` values from `inner` for every output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::RngCore;
+    /// use veranda::{SystemRng, Whitened};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut rng = Whitened::new(SystemRng::new(), 4);
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub fn new(inner: R, k: usize) -> Self {
+        assert!(k > 0, "Whitened must draw at least one value per output");
+        Self { inner, k }
+    }
+
+    /// Returns the inner RNG.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: RngCore> RngCore for Whitened<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut hasher = hasher();
+        hasher.write_u32(HASH_VERSION);
+        hasher.write_u64(self.k as u64);
+        for _ in 0..self.k {
+            hasher.write_u64(self.inner.next_u64());
+        }
+        hasher.finish()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let len = chunk.len();
+            let value = self.next_u64();
+            chunk.copy_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::Whitened;
+    use crate::SystemRng;
+
+    /// A source whose bits are each set only a quarter of the time.
+    struct Biased(SystemRng);
+
+    impl RngCore for Biased {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64() & self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest {
+                *byte = self.next_u64() as u8;
+            }
+        }
+    }
+
+    fn ones_fraction(mut rng: impl RngCore) -> f64 {
+        let ones: u32 = (0..1000).map(|_| rng.next_u64().count_ones()).sum();
+        f64::from(ones) / 64_000.0
+    }
+
+    #[test]
+    fn whitening_removes_bias() {
+        let raw = ones_fraction(Biased(SystemRng::new()));
+        let whitened = ones_fraction(Whitened::new(Biased(SystemRng::new()), 4));
+        assert!((raw - 0.25).abs() < 0.02, "{raw}");
+        assert!((whitened - 0.5).abs() < 0.02, "{whitened}");
+    }
+}
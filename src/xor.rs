@@ -0,0 +1,95 @@
+use rand::RngCore;
+
+/// An RNG that combines two independent RNGs by XORing their outputs together.
+///
+/// As long as the two inner RNGs are independent, the output is at least as unpredictable as the stronger of the two.
+/// This makes it easy to back a fast software RNG with a hardware one (or vice versa) for defense-in-depth.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct XorRng<A, B> {
+    a: A,
+    b: B,
+}
+impl<A: RngCore, B: RngCore> XorRng<A, B> {
+    /// Create a new `XorRng` combining the outputs of `a` and `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::{rngs::SmallRng, RngCore, SeedableRng};
+    /// use veranda::{SystemRng, XorRng};
+    /// use vexide::prelude::*;
+    ///
+    /// #[vexide::main]
+    /// async fn main(_: Peripherals) {
+    ///     let mut system = SystemRng::new();
+    ///     let fast = SmallRng::from_rng(&mut system);
+    ///     let mut rng = XorRng::new(system, fast);
+    ///     println!("Random number: {}", rng.next_u64());
+    /// }
+    /// ```
+    pub const fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+
+    /// Returns the two inner RNGs.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: RngCore, B: RngCore> RngCore for XorRng<A, B> {
+    fn next_u32(&mut self) -> u32 {
+        self.a.next_u32() ^ self.b.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.a.next_u64() ^ self.b.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.a.fill_bytes(dest);
+
+        let mut other = [0; 64];
+        for chunk in dest.chunks_mut(other.len()) {
+            let other = &mut other[..chunk.len()];
+            self.b.fill_bytes(other);
+            for (byte, other) in chunk.iter_mut().zip(other.iter()) {
+                *byte ^= other;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::mock::StepRng, RngCore};
+
+    use super::XorRng;
+    use crate::{backend::mock, SystemRng};
+
+    #[test]
+    fn output_differs_from_both_inputs() {
+        mock::install(mock::Mock::frozen());
+        let mut a = SystemRng::new().with_counter_start(1);
+        let mut b = SystemRng::new().with_counter_start(2);
+        let mut rng = XorRng::new(a.clone(), b.clone());
+        let (x, y) = (a.next_u64(), b.next_u64());
+        let combined = rng.next_u64();
+        assert_eq!(combined, x ^ y);
+        assert_ne!(combined, x);
+        assert_ne!(combined, y);
+    }
+
+    #[test]
+    fn a_constant_input_does_not_hide_the_other() {
+        let mut varying = StepRng::new(1, 0x9e37_79b9_7f4a_7c15);
+        let mut rng = XorRng::new(StepRng::new(0xdead_beef, 0), varying.clone());
+        for _ in 0..8 {
+            assert_eq!(rng.next_u64(), varying.next_u64() ^ 0xdead_beef);
+        }
+
+        let mut bytes = [0; 16];
+        XorRng::new(StepRng::new(0, 0), SystemRng::new()).fill_bytes(&mut bytes);
+        assert!(bytes.iter().any(|&byte| byte != bytes[0]));
+    }
+}